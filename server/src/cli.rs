@@ -0,0 +1,201 @@
+//! Command-line subcommands for troubleshooting outside the editor. Without
+//! arguments the binary runs as a language server instead.
+
+use crate::{explain, preview, schema, validate, Config};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: auto-header-server [COMMAND]
+
+Without a command, runs the language server on stdin/stdout.
+
+Commands:
+  render <path> [--root <dir>]
+      Print the header that would be inserted into <path> without writing
+      anything. Config files, template rule and profile go to stderr.
+      The workspace root defaults to the nearest ancestor containing
+      .auto-header.toml or .git; --root must contain <path>.
+  explain [--root <dir>]
+      Print every resolved config setting with the file it came from
+      (project, home, platform or default). The workspace root defaults to
+      the nearest directory containing .auto-header.toml or .git, starting
+      from the current one.
+  validate [--root <dir>] [<file>...]
+      Check config files for unknown keys, values of the wrong type,
+      unknown placeholders and filters in templates, and by_extension
+      entries for extensions without a built-in comment style (a warning). Without files, checks every
+      config file that applies to the workspace (found as for explain).
+      Exits with status 1 if there are errors.
+  schema
+      Print the JSON Schema for .auto-header.toml.
+  help
+      Show this message.";
+
+/// Run the subcommand in `args` (program name excluded).
+pub fn run(args: &[String]) -> ExitCode {
+    match args.first().map(String::as_str) {
+        Some("render") => render(&args[1..]),
+        Some("explain") => explain(&args[1..]),
+        Some("validate") => validate(&args[1..]),
+        Some("schema") => {
+            let schema = serde_json::to_string_pretty(schema::json_schema()).unwrap_or_default();
+            println!("{}", schema);
+            ExitCode::SUCCESS
+        }
+        Some("help" | "--help" | "-h") => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Some(other) => usage_error(&format!("unknown command `{}`", other)),
+        None => usage_error("missing command"),
+    }
+}
+
+fn render(args: &[String]) -> ExitCode {
+    let mut path = None;
+    let mut root = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => match args.next() {
+                Some(dir) => root = Some(absolute(Path::new(dir))),
+                None => return usage_error("--root needs a directory"),
+            },
+            _ if path.is_none() => path = Some(absolute(Path::new(arg))),
+            _ => return usage_error(&format!("unexpected argument `{}`", arg)),
+        }
+    }
+    let Some(path) = path else {
+        return usage_error("render needs a path");
+    };
+    if root.as_ref().is_some_and(|root| !path.starts_with(root)) {
+        return usage_error("--root must be a directory containing <path>");
+    }
+    let root = root.or_else(|| path.parent().and_then(find_workspace_root));
+
+    let preview = preview::preview(&path, root.as_deref());
+    eprint!("{}", preview);
+    print!("{}", preview.text);
+    ExitCode::SUCCESS
+}
+
+fn explain(args: &[String]) -> ExitCode {
+    let root = match args {
+        [] => std::env::current_dir()
+            .ok()
+            .and_then(|cwd| find_workspace_root(&cwd)),
+        [flag, dir] if flag == "--root" => Some(absolute(Path::new(dir))),
+        _ => return usage_error("explain only takes --root <dir>"),
+    };
+    print!("{}", explain::explain(root.as_deref()));
+    ExitCode::SUCCESS
+}
+
+fn validate(args: &[String]) -> ExitCode {
+    let mut root = None;
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => match args.next() {
+                Some(dir) => root = Some(absolute(Path::new(dir))),
+                None => return usage_error("--root needs a directory"),
+            },
+            _ => files.push(absolute(Path::new(arg))),
+        }
+    }
+
+    let problems = if files.is_empty() {
+        let root = root.or_else(|| {
+            std::env::current_dir()
+                .ok()
+                .and_then(|cwd| find_workspace_root(&cwd))
+        });
+        let sources = Config::sources(root.as_deref());
+        if sources.is_empty() {
+            eprintln!("No .auto-header.toml found");
+            return ExitCode::FAILURE;
+        }
+        for source in &sources {
+            eprintln!("Checking {}", source.path.display());
+        }
+        validate::validate_sources(&sources)
+    } else {
+        files
+            .iter()
+            .flat_map(|f| validate::validate_file(f))
+            .collect()
+    };
+
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    let errors = problems.iter().filter(|p| p.is_error()).count();
+    if !problems.is_empty() {
+        eprintln!(
+            "{} error(s), {} warning(s) found",
+            errors,
+            problems.len() - errors
+        );
+    }
+    if errors == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    ExitCode::from(2)
+}
+
+/// `path` relative to the current directory. The file doesn't need to exist.
+fn absolute(path: &Path) -> PathBuf {
+    std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Stand-in for the editor's workspace folder: `dir` or its nearest
+/// ancestor with a project config or a git checkout.
+fn find_workspace_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| dir.join(".auto-header.toml").is_file() || dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Workspace;
+
+    #[test]
+    fn workspace_root_is_nearest_config_or_git_ancestor() {
+        let ws = Workspace::new("cli-root");
+        ws.write("outer/.auto-header.toml", "");
+        ws.write("outer/inner/.git/HEAD", "");
+
+        assert_eq!(
+            find_workspace_root(&ws.root().join("outer/inner/src")),
+            Some(ws.root().join("outer/inner"))
+        );
+        assert_eq!(
+            find_workspace_root(&ws.root().join("outer")),
+            Some(ws.root().join("outer"))
+        );
+    }
+
+    #[test]
+    fn unknown_commands_are_usage_errors() {
+        let args = vec!["frobnicate".to_string()];
+        assert_eq!(run(&args), ExitCode::from(2));
+        let args = vec!["render".to_string()];
+        assert_eq!(run(&args), ExitCode::from(2));
+        let args = vec!["explain".to_string(), "--bogus".to_string()];
+        assert_eq!(run(&args), ExitCode::from(2));
+        let args = ["render", "/tmp/x/other/a.rs", "--root", "/tmp/x/root"].map(String::from);
+        assert_eq!(run(&args), ExitCode::from(2));
+    }
+}
@@ -0,0 +1,219 @@
+//! Resolve the owners of a path from a `CODEOWNERS` file.
+//!
+//! Supports the GitHub syntax (gitignore-style patterns, last match wins) and
+//! GitLab sections, where each `[Section]` is evaluated on its own and the
+//! owners of every matching section are combined.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// Locations checked for a CODEOWNERS file, in the order GitHub/GitLab use.
+const LOCATIONS: &[&str] = &[
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+struct Rule {
+    matcher: Gitignore,
+    /// `dir/*` only covers direct children, unlike `dir/` which is recursive.
+    direct_children_only: bool,
+    owners: Vec<String>,
+    section: usize,
+}
+
+/// Parsed CODEOWNERS rules for one repository.
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Load the first CODEOWNERS file found under `root`, if any.
+    pub fn load(root: &Path) -> Option<Self> {
+        LOCATIONS.iter().find_map(|location| {
+            let content = std::fs::read_to_string(root.join(location)).ok()?;
+            Some(Self::parse(root, &content))
+        })
+    }
+
+    pub fn parse(root: &Path, content: &str) -> Self {
+        let mut rules = Vec::new();
+        let mut section = 0;
+        let mut section_defaults: Vec<String> = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // GitLab section header: `[Name]`, `^[Optional]`, `[Name][2] @default-owner`
+            if let Some(header) = line.strip_prefix('^').unwrap_or(line).strip_prefix('[') {
+                section += 1;
+                let defaults = header.rsplit_once(']').map(|(_, rest)| rest).unwrap_or("");
+                section_defaults = tokenize(defaults);
+                continue;
+            }
+
+            let mut tokens = tokenize(line).into_iter();
+            let Some(pattern) = tokens.next() else {
+                continue;
+            };
+            let mut owners: Vec<String> = tokens.collect();
+            if owners.is_empty() {
+                owners = section_defaults.clone();
+            }
+
+            let mut builder = GitignoreBuilder::new(root);
+            if builder.add_line(None, &pattern).is_err() {
+                continue;
+            }
+            let Ok(matcher) = builder.build() else {
+                continue;
+            };
+            rules.push(Rule {
+                matcher,
+                direct_children_only: pattern.ends_with("/*"),
+                owners,
+                section,
+            });
+        }
+
+        Self { rules }
+    }
+
+    /// Owners of `relative_path` (relative to the repository root). Within a
+    /// section the last matching rule wins; matches from different GitLab
+    /// sections are combined in file order.
+    pub fn owners_of(&self, relative_path: &Path) -> Vec<String> {
+        let mut winners: Vec<&Rule> = Vec::new();
+        for rule in &self.rules {
+            let matched = if rule.direct_children_only {
+                rule.matcher.matched(relative_path, false).is_ignore()
+            } else {
+                rule.matcher
+                    .matched_path_or_any_parents(relative_path, false)
+                    .is_ignore()
+            };
+            if !matched {
+                continue;
+            }
+            match winners.iter_mut().find(|r| r.section == rule.section) {
+                Some(slot) => *slot = rule,
+                None => winners.push(rule),
+            }
+        }
+
+        let mut owners: Vec<String> = Vec::new();
+        for rule in winners {
+            for owner in &rule.owners {
+                if !owners.contains(owner) {
+                    owners.push(owner.clone());
+                }
+            }
+        }
+        owners
+    }
+}
+
+/// Split a CODEOWNERS line into whitespace-separated tokens, honouring `\ `
+/// escapes and stopping at an inline `#` comment.
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    // Keep `\#` escaped so the pattern isn't read as a comment
+                    if next != ' ' {
+                        current.push('\\');
+                    }
+                    current.push(next);
+                }
+            }
+            '#' if current.is_empty() => break,
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owners(content: &str, path: &str) -> Vec<String> {
+        CodeOwners::parse(Path::new("/repo"), content).owners_of(Path::new(path))
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let file = "\
+* @global
+*.rs @rustaceans
+/server/ @server-team
+";
+        assert_eq!(owners(file, "README.md"), vec!["@global"]);
+        assert_eq!(owners(file, "src/lib.rs"), vec!["@rustaceans"]);
+        assert_eq!(owners(file, "server/src/main.rs"), vec!["@server-team"]);
+    }
+
+    #[test]
+    fn unanchored_directory_matches_at_any_depth() {
+        let file = "apps/ @octocat\n";
+        assert_eq!(owners(file, "apps/x.js"), vec!["@octocat"]);
+        assert_eq!(owners(file, "nested/apps/deep/x.js"), vec!["@octocat"]);
+        assert!(owners(file, "applications/x.js").is_empty());
+    }
+
+    #[test]
+    fn single_star_directory_only_covers_direct_children() {
+        let file = "docs/* docs@example.com\n";
+        assert_eq!(owners(file, "docs/intro.md"), vec!["docs@example.com"]);
+        assert!(owners(file, "docs/build/intro.md").is_empty());
+    }
+
+    #[test]
+    fn rule_without_owners_clears_ownership() {
+        let file = "/lib/ @lib-owner\n/lib/generated/\n";
+        assert_eq!(owners(file, "lib/a.c"), vec!["@lib-owner"]);
+        assert!(owners(file, "lib/generated/a.c").is_empty());
+    }
+
+    #[test]
+    fn gitlab_sections_are_combined_with_defaults() {
+        let file = "\
+[Backend] @backend-team
+*.go
+/internal/ @core
+
+^[Docs][2] @docs-team
+*.md
+internal/*.go @gopher-writers # inline comment
+";
+        assert_eq!(owners(file, "cmd/main.go"), vec!["@backend-team"]);
+        assert_eq!(
+            owners(file, "internal/x.go"),
+            vec!["@core", "@gopher-writers"]
+        );
+        assert_eq!(owners(file, "README.md"), vec!["@docs-team"]);
+    }
+
+    #[test]
+    fn escaped_spaces_and_hashes_in_patterns() {
+        let file = "my\\ file.txt @spaces\n\\#notes @hash\n";
+        assert_eq!(owners(file, "my file.txt"), vec!["@spaces"]);
+        assert_eq!(owners(file, "#notes"), vec!["@hash"]);
+    }
+}
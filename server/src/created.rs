@@ -0,0 +1,148 @@
+//! The `[insert]` rules: telling files created in this session (reported
+//! through `workspace/didCreateFiles` or `workspace/willCreateFiles`) from
+//! pre-existing empty files like `__init__.py` or `.gitkeep`.
+
+use crate::{git, InsertConfig};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long a reported file counts as just created. Editors open new files
+/// right away; this keeps files that are never opened from piling up.
+const CREATED_TTL: Duration = Duration::from_secs(60);
+
+/// Files the client reported as created, with when they were reported.
+#[derive(Debug, Default)]
+pub struct CreatedFiles(HashMap<PathBuf, Instant>);
+
+impl CreatedFiles {
+    pub fn record(&mut self, path: PathBuf, now: Instant) {
+        self.prune(now);
+        self.0.insert(path, now);
+    }
+
+    /// Whether `path` was reported within `CREATED_TTL`; forgets it either
+    /// way, since only the first open of a new file counts.
+    pub fn take(&mut self, path: &Path, now: Instant) -> bool {
+        self.prune(now);
+        self.0.remove(path).is_some()
+    }
+
+    fn prune(&mut self, now: Instant) {
+        self.0
+            .retain(|_, reported| now.duration_since(*reported) < CREATED_TTL);
+    }
+}
+
+/// Why the empty `file` shouldn't get a header under `insert`, or `None` if
+/// it should. `created` is whether the client reported creating it, and
+/// `reports_creations` whether the client reports creations at all;
+/// without them `only_created` can't tell, so the other checks decide.
+pub fn skip_reason(
+    insert: &InsertConfig,
+    file: &Path,
+    created: bool,
+    reports_creations: bool,
+) -> Option<&'static str> {
+    // Files we saw being created are new by definition
+    if created {
+        return None;
+    }
+    if insert.only_created && reports_creations {
+        return Some("not created in this session");
+    }
+
+    if insert.max_age_secs > 0 {
+        let age = std::fs::metadata(file)
+            .and_then(|m| m.created().or_else(|_| m.modified()))
+            .ok()
+            .and_then(|t| t.elapsed().ok());
+        if age.is_some_and(|age| age.as_secs() > insert.max_age_secs) {
+            return Some("pre-existing file older than max_age_secs");
+        }
+    }
+
+    if insert.skip_git_tracked && git::is_tracked(file) {
+        return Some("file is tracked by git");
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Workspace;
+    use std::process::Command;
+
+    #[test]
+    fn created_files_are_taken_once_and_expire() {
+        let mut created = CreatedFiles::default();
+        let start = Instant::now();
+        created.record(PathBuf::from("/w/new.rs"), start);
+        created.record(PathBuf::from("/w/never_opened.rs"), start);
+
+        assert!(created.take(Path::new("/w/new.rs"), start));
+        assert!(!created.take(Path::new("/w/new.rs"), start));
+
+        let later = start + CREATED_TTL;
+        created.record(PathBuf::from("/w/other.rs"), later);
+        assert!(!created.take(Path::new("/w/never_opened.rs"), later));
+        assert_eq!(created.0.len(), 1);
+    }
+
+    #[test]
+    fn only_created_applies_when_the_client_reports_creations() {
+        let ws = Workspace::new("created-only");
+        let file = ws.write("pkg/__init__.py", "");
+        let insert = InsertConfig::default();
+
+        assert_eq!(skip_reason(&insert, &file, true, true), None);
+        assert_eq!(
+            skip_reason(&insert, &file, false, true),
+            Some("not created in this session")
+        );
+        // Not in a git repository, so nothing else skips it
+        assert_eq!(skip_reason(&insert, &file, false, false), None);
+    }
+
+    #[test]
+    fn age_and_git_checks_cover_other_empty_files() {
+        let ws = Workspace::new("created-checks");
+        let old = ws.write("old.py", "");
+        let fresh = ws.write("fresh.py", "");
+        let an_hour_ago = std::time::SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .and_then(|f| f.set_modified(an_hour_ago))
+            .unwrap();
+
+        let insert = InsertConfig {
+            only_created: false,
+            max_age_secs: 600,
+            skip_git_tracked: true,
+        };
+        // Filesystems without a birth time fall back to the modified time
+        if std::fs::metadata(&old).unwrap().created().is_err() {
+            assert!(skip_reason(&insert, &old, false, true).is_some());
+        }
+        assert!(skip_reason(&insert, &old, true, true).is_none());
+        assert_eq!(skip_reason(&insert, &fresh, false, true), None);
+
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(ws.root())
+                .args(args)
+                .output()
+                .is_ok_and(|out| out.status.success())
+        };
+        if git(&["init", "-q"]) && git(&["add", "fresh.py"]) {
+            assert_eq!(
+                skip_reason(&insert, &fresh, false, true),
+                Some("file is tracked by git")
+            );
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Minimal `.editorconfig` reader: the properties that apply to a file,
+//! gathered from its directory upwards until a file with `root = true`.
+//!
+//! Sections use EditorConfig globs (`*`, `**`, `?`, `[...]`, `{a,b}`); a
+//! pattern without `/` matches the file name in any subdirectory. Numeric
+//! ranges like `{1..3}` aren't supported. Keys and values are lowercased.
+
+use globset::GlobBuilder;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Properties for `file`, with closer `.editorconfig` files and later
+/// sections overriding earlier ones.
+pub fn properties(file: &Path) -> HashMap<String, String> {
+    let mut configs = Vec::new();
+    for dir in file.ancestors().skip(1) {
+        let Ok(content) = std::fs::read_to_string(dir.join(".editorconfig")) else {
+            continue;
+        };
+        let root = is_root(&content);
+        configs.push((dir, content));
+        if root {
+            break;
+        }
+    }
+
+    let mut properties = HashMap::new();
+    for (dir, content) in configs.iter().rev() {
+        let Ok(relative) = file.strip_prefix(dir) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        apply(content, &relative, &mut properties);
+    }
+    properties
+}
+
+fn is_root(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .any(|(key, value)| {
+            key.trim().eq_ignore_ascii_case("root") && value.trim().eq_ignore_ascii_case("true")
+        })
+}
+
+/// Apply the sections of one `.editorconfig` that match `relative`.
+fn apply(content: &str, relative: &str, properties: &mut HashMap<String, String>) {
+    let mut matches = false;
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            matches = section_matches(section, relative);
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| matches) {
+            properties.insert(key.trim().to_lowercase(), value.trim().to_lowercase());
+        }
+    }
+}
+
+fn section_matches(section: &str, relative: &str) -> bool {
+    let mut pattern = match section.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if section.contains('/') => section.to_string(),
+        None => format!("**/{}", section),
+    };
+    // EditorConfig's `**.rs` crosses directories; globset wants `**/*.rs`
+    let mut from = 0;
+    while let Some(i) = pattern[from..].find("**").map(|i| i + from) {
+        if pattern[i + 2..].starts_with(|c| c != '/') {
+            pattern.replace_range(i..i + 2, "**/*");
+        }
+        from = i + 2;
+    }
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .is_ok_and(|glob| glob.compile_matcher().is_match(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Workspace;
+
+    #[test]
+    fn sections_match_names_anywhere_and_paths_from_the_config() {
+        assert!(section_matches("*", "src/deep/main.rs"));
+        assert!(section_matches("*.{rs,toml}", "src/main.rs"));
+        assert!(!section_matches("*.{rs,toml}", "src/main.py"));
+        assert!(section_matches("src/*.rs", "src/main.rs"));
+        assert!(!section_matches("src/*.rs", "src/deep/main.rs"));
+        assert!(section_matches("/src/**.rs", "src/deep/main.rs"));
+        assert!(section_matches("Makefile", "tools/Makefile"));
+    }
+
+    #[test]
+    fn closer_files_and_later_sections_win_up_to_root() {
+        let ws = Workspace::new("editorconfig");
+        ws.write(
+            ".editorconfig",
+            "root = true\n\n[*]\nend_of_line = LF\nindent_style = space\n\n[*.bat]\nend_of_line = crlf\n",
+        );
+        ws.write(
+            "win/.editorconfig",
+            "; nested\n[*.ps1]\nend_of_line = crlf\n",
+        );
+
+        let props = properties(&ws.root().join("win/run.ps1"));
+        assert_eq!(props["end_of_line"], "crlf");
+        assert_eq!(props["indent_style"], "space");
+        assert_eq!(
+            properties(&ws.root().join("a/b.bat"))["end_of_line"],
+            "crlf"
+        );
+        assert_eq!(properties(&ws.root().join("main.rs"))["end_of_line"], "lf");
+    }
+}
@@ -0,0 +1,159 @@
+//! Line endings and byte order mark for inserted headers. Templates are
+//! rendered with `\n`; this converts them to what the file should use.
+
+use crate::{editorconfig, LineEnding};
+use std::io::Read;
+use std::path::Path;
+
+/// How many sibling files `auto` line endings look at.
+const SIBLING_SAMPLE: usize = 20;
+
+/// How many bytes of each sibling are read when detecting line endings.
+const SAMPLE_BYTES: u64 = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Encoding {
+    pub crlf: bool,
+    pub bom: bool,
+}
+
+impl Encoding {
+    /// Resolve the configured settings for `file`. `Auto` line endings use
+    /// `.editorconfig` `end_of_line`, then the majority of sibling files,
+    /// then LF. An unset `bom` follows `.editorconfig` `charset = utf-8-bom`.
+    pub fn resolve(file: &Path, line_ending: LineEnding, bom: Option<bool>) -> Self {
+        let editorconfig = editorconfig::properties(file);
+        let crlf = match line_ending {
+            LineEnding::Lf => false,
+            LineEnding::Crlf => true,
+            LineEnding::Auto => match editorconfig.get("end_of_line").map(String::as_str) {
+                Some("crlf") => true,
+                Some("lf") => false,
+                _ => siblings_use_crlf(file).unwrap_or(false),
+            },
+        };
+        let bom = bom.unwrap_or_else(|| {
+            editorconfig
+                .get("charset")
+                .is_some_and(|charset| charset == "utf-8-bom")
+        });
+        Self { crlf, bom }
+    }
+
+    /// `text` with CRLF line endings and a leading BOM if configured. A
+    /// shebang must be the very first bytes, so it never gets a BOM.
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() + 4);
+        if self.bom && !text.starts_with("#!") {
+            out.push('\u{feff}');
+        }
+        if self.crlf {
+            out.push_str(&text.replace("\r\n", "\n").replace('\n', "\r\n"));
+        } else {
+            out.push_str(text);
+        }
+        out
+    }
+
+    /// `LF`, `CRLF`, with ` + UTF-8 BOM` when one is written.
+    pub fn describe(&self) -> String {
+        let line_ending = if self.crlf { "CRLF" } else { "LF" };
+        if self.bom {
+            format!("{} + UTF-8 BOM", line_ending)
+        } else {
+            line_ending.to_string()
+        }
+    }
+}
+
+/// Whether most text files next to `file` use CRLF; `None` if none of them
+/// has a line ending.
+fn siblings_use_crlf(file: &Path) -> Option<bool> {
+    let entries = std::fs::read_dir(file.parent()?).ok()?;
+    let (mut crlf, mut lf) = (0, 0);
+    for path in entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path != file && path.is_file())
+        .take(SIBLING_SAMPLE)
+    {
+        let mut sample = Vec::new();
+        let read =
+            std::fs::File::open(&path).and_then(|f| f.take(SAMPLE_BYTES).read_to_end(&mut sample));
+        // Skip unreadable and binary files
+        if read.is_err() || sample.contains(&0) {
+            continue;
+        }
+        if sample.windows(2).any(|pair| pair == b"\r\n") {
+            crlf += 1;
+        } else if sample.contains(&b'\n') {
+            lf += 1;
+        }
+    }
+    (crlf + lf > 0).then_some(crlf > lf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Workspace;
+
+    #[test]
+    fn apply_converts_line_endings_and_adds_bom() {
+        let encoding = Encoding {
+            crlf: true,
+            bom: true,
+        };
+        assert_eq!(encoding.apply("a\nb\r\n"), "\u{feff}a\r\nb\r\n");
+        assert_eq!(encoding.describe(), "CRLF + UTF-8 BOM");
+        assert_eq!(encoding.apply("#!/bin/sh\n"), "#!/bin/sh\r\n");
+        let plain = Encoding {
+            crlf: false,
+            bom: false,
+        };
+        assert_eq!(plain.apply("a\n"), "a\n");
+    }
+
+    #[test]
+    fn auto_prefers_editorconfig_then_siblings() {
+        let ws = Workspace::new("encoding");
+        ws.write("win/a.cs", "class A {}\r\n");
+        ws.write("win/b.cs", "class B {}\r\n");
+        ws.write("win/c.cs", "class C {}\n");
+        ws.write("win/logo.bin", "\0\r\n");
+        let file = ws.root().join("win/new.cs");
+
+        let auto = Encoding::resolve(&file, LineEnding::Auto, None);
+        assert_eq!(
+            auto,
+            Encoding {
+                crlf: true,
+                bom: false
+            }
+        );
+        assert!(!Encoding::resolve(&file, LineEnding::Lf, None).crlf);
+
+        ws.write(
+            ".editorconfig",
+            "root = true\n[*.cs]\nend_of_line = lf\ncharset = utf-8-bom\n",
+        );
+        let auto = Encoding::resolve(&file, LineEnding::Auto, None);
+        assert_eq!(
+            auto,
+            Encoding {
+                crlf: false,
+                bom: true
+            }
+        );
+        assert!(!Encoding::resolve(&file, LineEnding::Auto, Some(false)).bom);
+        assert!(Encoding::resolve(&file, LineEnding::Crlf, None).crlf);
+    }
+
+    #[test]
+    fn empty_directory_defaults_to_lf() {
+        let ws = Workspace::new("encoding-empty");
+        ws.write("root/.editorconfig", "root = true\n");
+        let file = ws.root().join("root/new.rs");
+        assert!(!Encoding::resolve(&file, LineEnding::Auto, None).crlf);
+    }
+}
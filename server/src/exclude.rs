@@ -0,0 +1,168 @@
+//! Decide whether a file is excluded from header insertion: `[ignore]`
+//! patterns, the workspace's `.gitignore`/`.ignore` files, and files outside
+//! every workspace folder. Shared by `did_open` and any batch mode so both
+//! skip the same files.
+
+use crate::IgnoreConfig;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::Path;
+
+/// Why `file` must not get a header, or `None` if it may.
+pub fn skip_reason(
+    config: &IgnoreConfig,
+    file: &Path,
+    workspace_root: Option<&Path>,
+) -> Option<String> {
+    // The matchers below panic on paths outside their root
+    let inside = workspace_root.and_then(|root| Some((root, file.strip_prefix(root).ok()?)));
+    let Some((root, relative)) = inside else {
+        if config.outside_workspace {
+            return None;
+        }
+        return Some("outside of any workspace folder".to_string());
+    };
+
+    // Never touch git internals
+    if relative.components().any(|c| c.as_os_str() == ".git") {
+        return Some("inside .git".to_string());
+    }
+
+    if !config.patterns.is_empty() {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in &config.patterns {
+            // An invalid glob shouldn't disable the rest of the list
+            let _ = builder.add_line(None, pattern);
+        }
+        if let Ok(matcher) = builder.build() {
+            if let Match::Ignore(glob) = matcher.matched_path_or_any_parents(relative, false) {
+                return Some(format!("matches [ignore] pattern `{}`", glob.original()));
+            }
+        }
+    }
+
+    if config.gitignore {
+        if let Some(source) = gitignored_by(file, root) {
+            return Some(format!("ignored by {}", source));
+        }
+    }
+
+    None
+}
+
+/// The ignore file that excludes `file`, checking `.ignore` and `.gitignore`
+/// from the file's directory up to `root`. As with git, the closest file
+/// with a matching rule decides, and `.ignore` beats `.gitignore`.
+fn gitignored_by(file: &Path, root: &Path) -> Option<String> {
+    let dir = file.parent()?;
+    for ancestor in dir.ancestors() {
+        let Ok(relative) = file.strip_prefix(ancestor) else {
+            break;
+        };
+        if !ancestor.starts_with(root) {
+            break;
+        }
+        for name in [".ignore", ".gitignore"] {
+            let path = ancestor.join(name);
+            if !path.is_file() {
+                continue;
+            }
+            let (matcher, _) = Gitignore::new(&path);
+            match matcher.matched_path_or_any_parents(relative, false) {
+                Match::Ignore(_) => return Some(path.display().to_string()),
+                Match::Whitelist(_) => return None,
+                Match::None => {}
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Workspace;
+
+    fn config(patterns: &[&str]) -> IgnoreConfig {
+        IgnoreConfig {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            gitignore: true,
+            outside_workspace: false,
+        }
+    }
+
+    #[test]
+    fn configured_patterns_exclude_directories_and_globs() {
+        let ws = Workspace::new("exclude-patterns");
+        let config = config(&["vendor/", "*.generated.ts", "!keep.generated.ts"]);
+
+        let vendored = ws.write("vendor/lib/a.c", "");
+        let generated = ws.write("src/api.generated.ts", "");
+        let kept = ws.write("src/keep.generated.ts", "");
+        let normal = ws.write("src/main.c", "");
+
+        assert!(skip_reason(&config, &vendored, Some(ws.root()))
+            .unwrap()
+            .contains("vendor/"));
+        assert!(skip_reason(&config, &generated, Some(ws.root())).is_some());
+        assert_eq!(skip_reason(&config, &kept, Some(ws.root())), None);
+        assert_eq!(skip_reason(&config, &normal, Some(ws.root())), None);
+    }
+
+    #[test]
+    fn gitignore_files_are_respected_unless_disabled() {
+        let ws = Workspace::new("exclude-gitignore");
+        ws.write(".gitignore", "build/\n*.log\n");
+        ws.write("src/.gitignore", "!debug.log\n");
+        ws.write(".ignore", "generated/\n");
+
+        let built = ws.write("build/out.c", "");
+        let log = ws.write("run.log", "");
+        let whitelisted = ws.write("src/debug.log", "");
+        let generated = ws.write("generated/x.rs", "");
+
+        let mut config = config(&[]);
+        assert!(skip_reason(&config, &built, Some(ws.root())).is_some());
+        assert!(skip_reason(&config, &log, Some(ws.root())).is_some());
+        assert_eq!(skip_reason(&config, &whitelisted, Some(ws.root())), None);
+        assert!(skip_reason(&config, &generated, Some(ws.root()))
+            .unwrap()
+            .ends_with(".ignore"));
+
+        config.gitignore = false;
+        assert_eq!(skip_reason(&config, &built, Some(ws.root())), None);
+    }
+
+    #[test]
+    fn files_outside_workspace_are_skipped_by_default() {
+        let mut config = config(&[]);
+        let file = Path::new("/tmp/scratch.py");
+        assert!(skip_reason(&config, file, None).is_some());
+
+        config.outside_workspace = true;
+        assert_eq!(skip_reason(&config, file, None), None);
+    }
+
+    #[test]
+    fn files_outside_the_given_root_are_outside_the_workspace() {
+        let ws = Workspace::new("exclude-outside");
+        ws.write("root/.gitignore", "*.log\n");
+        let mut config = config(&["vendor/"]);
+        let file = ws.write("other/vendor/run.log", "");
+        let root = ws.root().join("root");
+        assert_eq!(
+            skip_reason(&config, &file, Some(&root)).as_deref(),
+            Some("outside of any workspace folder")
+        );
+
+        config.outside_workspace = true;
+        assert_eq!(skip_reason(&config, &file, Some(&root)), None);
+    }
+
+    #[test]
+    fn git_directory_is_always_skipped() {
+        let config = config(&[]);
+        let file = Path::new("/ws/.git/COMMIT_EDITMSG");
+        assert!(skip_reason(&config, file, Some(Path::new("/ws"))).is_some());
+    }
+}
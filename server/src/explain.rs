@@ -0,0 +1,219 @@
+//! Where each setting of the merged config came from, for the `explain`
+//! subcommand and the `auto-header.explain` command.
+//!
+//! Mirrors `PartialConfig::merge`: the highest-priority file that sets a key
+//! wins, `[prompts.<label>]` tables are taken whole from one file, and
+//! `[ignore] patterns` accumulate across every file that sets them.
+
+use crate::{Config, ConfigSource};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    /// Config files that were found, highest priority first.
+    pub config_files: Vec<String>,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Field {
+    /// Dotted key, e.g. `header.by_extension.rs.template`.
+    pub key: String,
+    /// The resolved value as JSON.
+    pub value: String,
+    /// `project`, `home`, `platform` or `default`; accumulated values list
+    /// every contributing scope, e.g. `project + home`.
+    pub origin: String,
+}
+
+/// Resolve the config for `workspace_root` and attribute every field.
+pub fn explain(workspace_root: Option<&Path>) -> Explanation {
+    explain_sources(&Config::sources(workspace_root))
+}
+
+/// `explain` for already-loaded `sources`, highest priority first.
+fn explain_sources(sources: &[ConfigSource]) -> Explanation {
+    let config = Config::from_sources(sources).resolve_env();
+
+    // The raw tables tell us which keys each file actually sets
+    let tables: Vec<(&str, toml::Table)> = sources
+        .iter()
+        .filter(|source| source.partial.is_ok())
+        .filter_map(|source| {
+            let content = std::fs::read_to_string(&source.path).ok()?;
+            Some((source.scope, toml::from_str(&content).ok()?))
+        })
+        .collect();
+
+    let resolved = match toml::Value::try_from(&config) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => toml::Table::new(),
+    };
+    let mut leaves = Vec::new();
+    flatten(&mut Vec::new(), &resolved, &mut leaves);
+
+    let fields = leaves
+        .into_iter()
+        .map(|(path, value)| Field {
+            key: path.join("."),
+            value: serde_json::to_string(value).unwrap_or_default(),
+            origin: origin(&path, &tables),
+        })
+        .collect();
+
+    Explanation {
+        config_files: sources.iter().map(ConfigSource::describe).collect(),
+        fields,
+    }
+}
+
+/// Collect every non-table value under `table` with its key path. Arrays are
+/// leaves: they are never merged element by element.
+fn flatten<'a>(
+    prefix: &mut Vec<String>,
+    table: &'a toml::Table,
+    out: &mut Vec<(Vec<String>, &'a toml::Value)>,
+) {
+    for (key, value) in table {
+        prefix.push(key.clone());
+        match value {
+            toml::Value::Table(inner) => flatten(prefix, inner, out),
+            _ => out.push((prefix.clone(), value)),
+        }
+        prefix.pop();
+    }
+}
+
+fn origin(path: &[String], tables: &[(&str, toml::Table)]) -> String {
+    // A prompt label's options and default come from the same file
+    let unit = if path.len() > 2 && path[0] == "prompts" {
+        &path[..2]
+    } else {
+        path
+    };
+    let mut setters = tables
+        .iter()
+        .filter(|(_, table)| sets(table, unit))
+        .map(|(scope, _)| *scope);
+
+    if unit == ["ignore", "patterns"] {
+        let scopes: Vec<&str> = setters.collect();
+        if !scopes.is_empty() {
+            return scopes.join(" + ");
+        }
+    } else if let Some(scope) = setters.next() {
+        return scope.to_string();
+    }
+    "default".to_string()
+}
+
+/// Whether `table` sets the key at `path`.
+fn sets(table: &toml::Table, path: &[String]) -> bool {
+    let Some((last, parents)) = path.split_last() else {
+        return false;
+    };
+    let mut current = table;
+    for key in parents {
+        match current.get(key) {
+            Some(toml::Value::Table(inner)) => current = inner,
+            _ => return false,
+        }
+    }
+    current.contains_key(last)
+}
+
+/// `key = value  # origin`, one line per field, after the config files.
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.config_files.is_empty() {
+            writeln!(f, "# Config files: (none, using built-in defaults)")?;
+        } else {
+            writeln!(f, "# Config files (highest priority first):")?;
+            for file in &self.config_files {
+                writeln!(f, "#   {}", file)?;
+            }
+        }
+        for field in &self.fields {
+            writeln!(f, "{} = {}  # {}", field.key, field.value, field.origin)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{project_sources, Workspace};
+
+    fn table(toml: &str) -> toml::Table {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn path(key: &str) -> Vec<String> {
+        key.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn highest_priority_file_setting_a_key_wins() {
+        let tables = vec![
+            ("project", table("[author]\nname = \"P\"")),
+            (
+                "home",
+                table("[author]\nname = \"H\"\nemail = \"h@x\"\n[project]\nname = \"X\""),
+            ),
+            ("platform", table("[project]\nname = \"Y\"")),
+        ];
+        assert_eq!(origin(&path("author.name"), &tables), "project");
+        assert_eq!(origin(&path("author.email"), &tables), "home");
+        assert_eq!(origin(&path("project.name"), &tables), "home");
+        assert_eq!(origin(&path("header.template"), &tables), "default");
+    }
+
+    #[test]
+    fn by_extension_fields_are_attributed_separately() {
+        let tables = vec![
+            (
+                "project",
+                table("[header.by_extension.rs]\nenabled = false"),
+            ),
+            ("home", table("[header.by_extension.rs]\ntemplate = \"T\"")),
+        ];
+        assert_eq!(
+            origin(&path("header.by_extension.rs.enabled"), &tables),
+            "project"
+        );
+        assert_eq!(
+            origin(&path("header.by_extension.rs.template"), &tables),
+            "home"
+        );
+    }
+
+    #[test]
+    fn prompts_are_whole_tables_and_patterns_accumulate() {
+        let tables = vec![
+            (
+                "project",
+                table("[prompts.Team]\noptions = [\"a\"]\n[ignore]\npatterns = [\"x\"]"),
+            ),
+            (
+                "home",
+                table("[prompts.Team]\ndefault = \"b\"\n[ignore]\npatterns = [\"y\"]"),
+            ),
+        ];
+        assert_eq!(origin(&path("prompts.Team.default"), &tables), "project");
+        assert_eq!(origin(&path("ignore.patterns"), &tables), "project + home");
+    }
+
+    #[test]
+    fn explanation_lists_every_resolved_field() {
+        let ws = Workspace::new("explain-fields");
+        ws.write(".auto-header.toml", "[author]\nname = \"A\"\n");
+        let explanation = explain_sources(&project_sources(ws.root()));
+        let field = |key: &str| explanation.fields.iter().find(|f| f.key == key);
+        assert_eq!(field("author.name").unwrap().origin, "project");
+        assert_eq!(field("ignore.gitignore").unwrap().value, "true");
+        assert!(explanation.to_string().contains("\nenabled = true  # "));
+    }
+}
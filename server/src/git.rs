@@ -0,0 +1,20 @@
+//! Helpers that shell out to `git`.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Whether `path` is tracked in its git repository. Returns `false` when git
+/// is unavailable or the file is outside a repository. Blocks until git
+/// exits, so the server only calls it through `blocking`.
+pub fn is_tracked(path: &Path) -> bool {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["ls-files", "--error-unmatch", "--"])
+        .arg(name)
+        .output()
+        .is_ok_and(|out| out.status.success())
+}
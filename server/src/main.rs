@@ -1,4 +1,5 @@
 use chrono::Local;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -7,6 +8,25 @@ use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
+mod cli;
+mod codeowners;
+mod created;
+mod editorconfig;
+mod encoding;
+mod exclude;
+mod explain;
+mod git;
+mod package;
+mod preview;
+mod reflow;
+mod schema;
+mod shebang;
+mod snippet;
+mod template;
+#[cfg(test)]
+mod test_support;
+mod validate;
+
 /// The resolved config directory, combining custom override or platform defaults.
 /// This is set once and cached for subsequent calls.
 /// On macOS, this is `~/.config/zed`.
@@ -43,8 +63,18 @@ struct HeaderProfile {
     prologue: &'static str,
     comment_style: CommentStyle,
     epilogue: &'static str,
+    /// Script languages start with `#!/usr/bin/env {interpreter}` unless
+    /// the extension's `shebang` says otherwise.
+    script: bool,
+    /// Start with `CODING_COOKIE`, below any shebang.
+    coding_cookie: bool,
 }
 
+/// PEP 263 source encoding declaration. Python 3 reads UTF-8 by default,
+/// so only the built-in Python style writes it unless `encoding_cookie`
+/// says otherwise.
+const CODING_COOKIE: &str = "# -*- coding: utf-8 -*-";
+
 impl HeaderProfile {
     fn block(
         prologue: &'static str,
@@ -58,8 +88,24 @@ impl HeaderProfile {
                 start,
                 end,
                 line_prefix,
+                nested: false,
             },
             epilogue: "\n",
+            script: false,
+            coding_cookie: false,
+        }
+    }
+
+    /// A `block` in a language whose block comments nest.
+    fn nested_block(start: &'static str, end: &'static str, line_prefix: &'static str) -> Self {
+        Self {
+            comment_style: CommentStyle::Block {
+                start,
+                end,
+                line_prefix,
+                nested: true,
+            },
+            ..Self::block("", start, end, line_prefix)
         }
     }
 
@@ -68,6 +114,16 @@ impl HeaderProfile {
             prologue,
             comment_style: CommentStyle::Line { prefix },
             epilogue,
+            script: false,
+            coding_cookie: false,
+        }
+    }
+
+    /// `#` comments framed by bare `#` lines, below the shebang.
+    fn script() -> Self {
+        Self {
+            script: true,
+            ..Self::line("#\n", "#", "#\n\n")
         }
     }
 
@@ -76,20 +132,45 @@ impl HeaderProfile {
             prologue: "",
             comment_style: CommentStyle::HtmlComment,
             epilogue: "\n",
+            script: false,
+            coding_cookie: false,
         }
     }
 
+    /// Extensions with a dedicated arm in `builtin`; everything else
+    /// gets `#` line comments. Offered as completions for
+    /// `[header.by_extension.<ext>]`.
+    const KNOWN_EXTENSIONS: &'static [&'static str] = &[
+        "c", "h", "cpp", "hpp", "cc", "hh", "cxx", "hxx", "cs", "java", "js", "jsx", "ts", "tsx",
+        "rs", "scala", "kt", "kts", "swift", "go", "m", "mm", "d", "zig", "dart", "css", "scss",
+        "sass", "less", "php", "phtml", "php3", "php4", "php5", "phps", "phpt", "py", "pyw", "pyx",
+        "sh", "bash", "zsh", "fish", "rb", "pl", "pm", "r", "R", "jl", "yaml", "yml", "toml",
+        "ini", "conf", "cfg", "tcl", "nim", "crystal", "html", "htm", "xml", "svg", "xhtml", "sql",
+        "lua", "hs", "lhs", "ads", "adb", "lisp", "cl", "scm", "clj", "cljs", "rkt", "gleam",
+        "erl", "hrl", "ex", "exs", "vim", "v", "vh", "sv", "svh", "odin", "ml", "mli", "fs", "fsi",
+        "fsx", "tex", "latex", "sty", "cls", "bib", "typ",
+    ];
+
     /// Get the header profile for a file extension.
     fn for_extension(ext: &str) -> Self {
-        match ext {
+        // Default: line comment with #
+        Self::builtin(ext).unwrap_or_else(|| Self::line("", "#", "\n"))
+    }
+
+    /// The dedicated profile for `ext`, if it has one.
+    fn builtin(ext: &str) -> Option<Self> {
+        let profile = match ext {
             // C-style languages
             "c" | "h" | "cpp" | "hpp" | "cc" | "hh" | "cxx" | "hxx" => {
                 Self::block("", "/*", "*/", " *")
             }
-            "cs" | "java" | "js" | "jsx" | "ts" | "tsx" | "rs" | "scala" | "kt" | "kts"
-            | "swift" | "go" | "m" | "mm" | "d" | "zig" | "dart" => {
+            "cs" | "java" | "js" | "jsx" | "ts" | "tsx" | "go" | "m" | "mm" | "d" | "zig" => {
                 Self::block("", "/*", "*/", " *")
             }
+            // Block comments nest in these
+            "rs" | "scala" | "kt" | "kts" | "swift" | "dart" => {
+                Self::nested_block("/*", "*/", " *")
+            }
             "css" | "scss" | "sass" | "less" => Self::block("", "/*", "*/", " *"),
 
             // PHP: requires an opening tag before the comment block
@@ -98,19 +179,16 @@ impl HeaderProfile {
             }
 
             // Python
-            "py" | "pyw" | "pyx" => {
-                Self::block("# -*- coding: utf-8 -*-\n", "\"\"\"", "\"\"\"", "")
-            }
-
-            // Shell scripts
-            "sh" | "bash" | "zsh" => Self::line("#!/usr/bin/env bash\n#\n", "#", "#\n\n"),
-            "fish" => Self::line("#!/usr/bin/env fish\n#\n", "#", "#\n\n"),
+            "py" | "pyw" | "pyx" => Self {
+                coding_cookie: true,
+                ..Self::block("", "\"\"\"", "\"\"\"", "")
+            },
 
-            // Other scripting languages with shebang
-            "rb" => Self::line("#!/usr/bin/env ruby\n#\n", "#", "#\n\n"),
-            "pl" | "pm" => Self::line("#!/usr/bin/env perl\n#\n", "#", "#\n\n"),
-            "r" | "R" => Self::line("#!/usr/bin/env Rscript\n#\n", "#", "#\n\n"),
-            "jl" => Self::line("#!/usr/bin/env julia\n#\n", "#", "#\n\n"),
+            // Shell and other scripting languages, with a shebang for
+            // `shebang::default_interpreter`
+            "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "pm" | "r" | "R" | "jl" => {
+                Self::script()
+            }
 
             // Simple line comments with #
             "yaml" | "yml" | "toml" | "ini" | "conf" | "cfg" | "tcl" | "nim" | "crystal" => {
@@ -144,24 +222,121 @@ impl HeaderProfile {
             "odin" => Self::line("", "//", "\n"),
 
             // OCaml/F#
-            "ml" | "mli" | "fs" | "fsi" | "fsx" => Self::block("", "(*", "*)", " *"),
+            "ml" | "mli" | "fs" | "fsi" | "fsx" => Self::nested_block("(*", "*)", " *"),
 
             // LaTeX/TeX/Typst
             "tex" | "latex" | "sty" | "cls" | "bib" | "typ" => Self::line("", "%", "\n"),
 
-            // Default: line comment with #
-            _ => Self::line("", "#", "\n"),
+            _ => return None,
+        };
+        Some(profile)
+    }
+
+    /// A built-in variant selected with `profile = "..."` under
+    /// `[header.by_extension.<ext>]`.
+    fn named(name: NamedProfile) -> Self {
+        match name {
+            NamedProfile::RustDoc => Self::line("", "//!", "\n"),
+            NamedProfile::Jsdoc => Self::block("", "/**", "*/", " *"),
+            NamedProfile::PythonDocstring => Self::block("", "\"\"\"", "\"\"\"", ""),
+            NamedProfile::PythonComment => Self::line("", "#", "\n"),
+            // No blank line: Go attaches the comment to the `package` clause
+            NamedProfile::GoDoc => Self::line("", "//", ""),
+        }
+    }
+
+    /// Human-readable summary for previews, e.g. `block comment /* */`.
+    fn describe(&self) -> String {
+        let style = self.comment_style.describe();
+        // The bare `#` line scripts put under their shebang isn't worth naming
+        let notable = |l: &&str| !l.trim().is_empty() && l.trim() != "#";
+        let cookie = self.coding_cookie.then_some(CODING_COOKIE);
+        match cookie
+            .into_iter()
+            .chain(self.prologue.lines())
+            .find(notable)
+        {
+            Some(first) => format!("{} after `{}`", style, first),
+            None => style,
         }
     }
 
-    fn wrap(&self, content: &str) -> String {
-        let mut result = String::from(self.prologue);
-        result.push_str(&self.comment_style.wrap(content));
+    fn wrap(&self, content: &str, layout: &Layout) -> String {
+        let mut result = String::new();
+        if self.coding_cookie {
+            result.push_str(CODING_COOKIE);
+            result.push('\n');
+        }
+        result.push_str(self.prologue);
+        result.push_str(&self.comment_style.wrap(content, layout));
         result.push_str(self.epilogue);
         result
     }
 }
 
+/// Default width of `box_style` banners when no line length is configured.
+const BANNER_WIDTH: usize = 80;
+
+/// Rules that shape the comment: `.editorconfig` indentation and line
+/// length, plus the `[header]` width and banner settings.
+#[derive(Debug, Clone)]
+struct Layout {
+    /// One level of indentation for comment styles that indent their
+    /// content (HTML).
+    indent: String,
+    /// Columns `indent` takes up.
+    indent_width: usize,
+    /// Longest allowed line, comment markers included.
+    max_line_length: Option<usize>,
+    /// Refill whole paragraphs to `max_line_length` (`wrap_width`) rather
+    /// than only breaking the lines that are too long.
+    reflow: bool,
+    box_style: BoxStyle,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            indent_width: 2,
+            max_line_length: None,
+            reflow: false,
+            box_style: BoxStyle::None,
+        }
+    }
+}
+
+impl Layout {
+    /// `indent_style`, `indent_size`/`tab_width` and `max_line_length` from
+    /// the `.editorconfig` files that apply to `file`.
+    fn for_file(file: &Path) -> Self {
+        let props = editorconfig::properties(file);
+        let number = |key: &str| props.get(key).and_then(|v| v.parse::<usize>().ok());
+        let tab_width = number("tab_width").or(number("indent_size")).unwrap_or(4);
+        // `indent_size = tab` means "same as tab_width"
+        let size = match props.get("indent_size").map(String::as_str) {
+            Some("tab") => Some(tab_width),
+            _ => number("indent_size").or(number("tab_width")),
+        };
+
+        let mut layout = Self {
+            max_line_length: number("max_line_length"),
+            ..Self::default()
+        };
+        if props
+            .get("indent_style")
+            .is_some_and(|style| style == "tab")
+        {
+            layout.indent = "\t".to_string();
+            layout.indent_width = tab_width;
+        } else if let Some(size) = size {
+            layout.indent = " ".repeat(size);
+            layout.indent_width = size;
+        }
+        layout
+    }
+}
+
 /// Comment body style used inside a header profile.
 #[derive(Debug, Clone)]
 enum CommentStyle {
@@ -170,6 +345,9 @@ enum CommentStyle {
         start: &'static str,
         end: &'static str,
         line_prefix: &'static str,
+        /// Comments nest, so an opening marker in the content needs a
+        /// matching close and is escaped too.
+        nested: bool,
     },
     /// Line comment style with a prefix (e.g., # or //)
     Line { prefix: &'static str },
@@ -178,13 +356,113 @@ enum CommentStyle {
 }
 
 impl CommentStyle {
-    /// Wrap content with this comment style.
-    fn wrap(&self, content: &str) -> String {
+    fn describe(&self) -> String {
+        match self {
+            Self::Block { start, end, .. } => format!("block comment {} {}", start, end),
+            Self::Line { prefix } => format!("line comment {}", prefix),
+            Self::HtmlComment => "HTML comment <!-- -->".to_string(),
+        }
+    }
+
+    /// Break up anything in `content` that would end the comment early,
+    /// returning the style to wrap it with. Python docstrings containing
+    /// `"""` switch to `'''` instead, and only escape quotes if they contain
+    /// both. Opening markers are escaped too where comments nest (`nested`:
+    /// Rust, Swift, OCaml, ...), and left alone elsewhere.
+    fn escape(&self, content: &str) -> (Self, String) {
+        match *self {
+            Self::Block {
+                start: "\"\"\"",
+                line_prefix,
+                ..
+            } if content.contains("\"\"\"") => {
+                if content.contains("'''") {
+                    (self.clone(), content.replace('"', "\\\""))
+                } else {
+                    let style = Self::Block {
+                        start: "'''",
+                        end: "'''",
+                        line_prefix,
+                        nested: false,
+                    };
+                    (style, content.to_string())
+                }
+            }
+            Self::Block {
+                start: "/*" | "/**",
+                nested,
+                ..
+            } => {
+                let content = content.replace("*/", "*\\/");
+                match nested {
+                    true => (self.clone(), content.replace("/*", "/\\*")),
+                    false => (self.clone(), content),
+                }
+            }
+            Self::Block {
+                start: "(*",
+                nested,
+                ..
+            } => {
+                let content = content.replace("*)", "* )");
+                match nested {
+                    true => (self.clone(), content.replace("(*", "( *")),
+                    false => (self.clone(), content),
+                }
+            }
+            Self::Block { start: "--[[", .. } => {
+                // Lua long comments end at the first `]]`
+                let mut content = content.to_string();
+                while content.contains("]]") {
+                    content = content.replace("]]", "] ]");
+                }
+                (self.clone(), content)
+            }
+            Self::HtmlComment => (
+                self.clone(),
+                content.replace("-->", "--&gt;").replace("--!>", "--!&gt;"),
+            ),
+            _ => (self.clone(), content.to_string()),
+        }
+    }
+
+    /// Columns each content line is shifted right by.
+    fn prefix_width(&self, layout: &Layout) -> usize {
         match self {
+            Self::Block {
+                line_prefix: "", ..
+            } => 0,
+            Self::Block { line_prefix, .. } => line_prefix.chars().count() + 1,
+            Self::Line { prefix } => prefix.chars().count() + 1,
+            Self::HtmlComment => layout.indent_width,
+        }
+    }
+
+    /// Wrap content with this comment style, breaking lines that would
+    /// exceed `layout.max_line_length`.
+    fn wrap(&self, content: &str, layout: &Layout) -> String {
+        let (style, mut content) = self.escape(content);
+        if let Some(max) = layout.max_line_length {
+            let edge = match style.right_edge() {
+                Some(edge) if layout.box_style == BoxStyle::Box => edge.chars().count() + 1,
+                _ => 0,
+            };
+            let width = max.saturating_sub(style.prefix_width(layout) + edge).max(1);
+            content = if layout.reflow {
+                reflow::reflow(&content, width)
+            } else {
+                reflow::wrap_long_lines(&content, width)
+            };
+        }
+        if layout.box_style != BoxStyle::None {
+            return style.wrap_boxed(&content, layout);
+        }
+        match style {
             Self::Block {
                 start,
                 end,
                 line_prefix,
+                ..
             } => {
                 let mut result = format!("{}\n", start);
                 for line in content.lines() {
@@ -227,7 +505,7 @@ impl CommentStyle {
                 let mut result = String::from("<!--\n");
                 for line in content.lines() {
                     if !line.trim().is_empty() {
-                        result.push_str("  ");
+                        result.push_str(&layout.indent);
                         result.push_str(line);
                     }
                     result.push('\n');
@@ -237,50 +515,414 @@ impl CommentStyle {
             }
         }
     }
+
+    /// The text a `box` banner repeats at the right end of each line, if
+    /// this style has a line prefix to mirror.
+    fn right_edge(&self) -> Option<&'static str> {
+        match self {
+            Self::Block {
+                line_prefix: "", ..
+            }
+            | Self::HtmlComment => None,
+            Self::Block { line_prefix, .. } => Some(line_prefix.trim()),
+            Self::Line { prefix } => Some(prefix),
+        }
+    }
+
+    /// Banner version of `wrap` for `layout.box_style`: rules of `*`, `=` or
+    /// `-` above and below the content, as wide as `max_line_length` (or
+    /// `BANNER_WIDTH`) unless the content needs more. `box` also closes each
+    /// line with a right edge aligned to the rules.
+    fn wrap_boxed(&self, content: &str, layout: &Layout) -> String {
+        let fill = layout.box_style.fill();
+        let edge = self
+            .right_edge()
+            .filter(|_| layout.box_style == BoxStyle::Box);
+        let edge_width = edge.map_or(0, |e| e.chars().count() + 1);
+        let longest = content
+            .lines()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = layout
+            .max_line_length
+            .unwrap_or(BANNER_WIDTH)
+            .max(self.prefix_width(layout) + longest + edge_width);
+
+        // `prefix text   edge`, padded so the edges line up at `width`
+        let boxed_line = |prefix: &str, line: &str| {
+            let mut out = prefix.to_string();
+            if !line.trim().is_empty() {
+                out.push(' ');
+                out.push_str(line);
+            }
+            if let Some(edge) = edge {
+                let used = out.chars().count() + edge.chars().count();
+                out.push_str(&" ".repeat(width.saturating_sub(used)));
+                out.push_str(edge);
+            }
+            out.push('\n');
+            out
+        };
+        let rule = |len: usize| fill.to_string().repeat(len);
+
+        let mut result = String::new();
+        match self {
+            Self::Block {
+                start,
+                end,
+                line_prefix,
+                ..
+            } if !line_prefix.is_empty() => {
+                result.push_str(start);
+                result.push_str(&rule(width.saturating_sub(start.chars().count())));
+                result.push('\n');
+                for line in content.lines() {
+                    result.push_str(&boxed_line(line_prefix, line));
+                }
+                result.push(' ');
+                result.push_str(&rule(width.saturating_sub(end.chars().count() + 1)));
+                result.push_str(end);
+                result.push('\n');
+            }
+            Self::Line { prefix } => {
+                let rule_line = match edge {
+                    Some(edge) => format!(
+                        "{} {} {}\n",
+                        prefix,
+                        rule(width.saturating_sub(prefix.chars().count() + edge_width + 1)),
+                        edge
+                    ),
+                    None => format!(
+                        "{} {}\n",
+                        prefix,
+                        rule(width.saturating_sub(prefix.chars().count() + 1))
+                    ),
+                };
+                result.push_str(&rule_line);
+                for line in content.lines() {
+                    result.push_str(&boxed_line(prefix, line));
+                }
+                result.push_str(&rule_line);
+            }
+            // Docstrings, Lua and HTML have no line prefix: the rules go on
+            // their own lines inside the comment
+            _ => {
+                let (start, end, indent) = match self {
+                    Self::Block { start, end, .. } => (*start, *end, ""),
+                    _ => ("<!--", "-->", layout.indent.as_str()),
+                };
+                let rule_line = format!(
+                    "{}{}\n",
+                    indent,
+                    rule(width.saturating_sub(self.prefix_width(layout)))
+                );
+                result.push_str(start);
+                result.push('\n');
+                result.push_str(&rule_line);
+                for line in content.lines() {
+                    if !line.trim().is_empty() {
+                        result.push_str(indent);
+                        result.push_str(line);
+                    }
+                    result.push('\n');
+                }
+                result.push_str(&rule_line);
+                result.push_str(end);
+                result.push('\n');
+            }
+        }
+        result
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct AuthorConfig {
+    /// Name shown by `{author}` / `{authors}`.
     name: String,
+    /// Email shown by `{email}` and after the name in `{authors}`.
+    #[serde(default)]
     email: String,
 }
 
+impl AuthorConfig {
+    /// Render as `Name <email>`, or just `Name` when no email is configured.
+    fn display(&self) -> String {
+        if self.email.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} <{}>", self.name, self.email)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 struct ProjectConfig {
     name: String,
     #[serde(default)]
     copyright_holder: String,
+    /// Directories that `{package}`/`{namespace}`/`{module}` are relative to.
+    #[serde(default = "package::default_source_roots")]
+    source_roots: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 struct HeaderConfig {
     template: String,
+    /// What to do with placeholders that don't resolve, like `{typo}`.
+    #[serde(default)]
+    unresolved: UnresolvedPolicy,
+    #[serde(default)]
+    line_ending: LineEnding,
+    /// `None` follows `.editorconfig` `charset`.
+    #[serde(default)]
+    bom: Option<bool>,
+    /// Reflow paragraphs to this many columns; `None` or 0 is off.
+    #[serde(default)]
+    wrap_width: Option<usize>,
+    #[serde(default)]
+    box_style: BoxStyle,
     #[serde(default)]
     by_extension: HashMap<String, ExtensionHeaderConfig>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Handling of placeholders left unresolved after rendering. Every policy
+/// logs them and warns the user; `error` also refuses to insert the header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum UnresolvedPolicy {
+    /// Leave them in the header verbatim.
+    #[default]
+    Keep,
+    /// Drop them from the header.
+    Remove,
+    /// Don't insert the header at all.
+    Error,
+}
+
+/// Line endings of the inserted header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum LineEnding {
+    /// `.editorconfig` `end_of_line`, else whatever most files in the same
+    /// directory use, else LF.
+    #[default]
+    Auto,
+    /// `\n`.
+    Lf,
+    /// `\r\n`.
+    Crlf,
+}
+
+/// Built-in doc-comment profiles that replace an extension's default.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+enum NamedProfile {
+    /// Rust module docs: `//!` inner doc comments.
+    RustDoc,
+    /// `/** ... */` JSDoc/Javadoc with `@file`, `@author` and `@date` tags.
+    Jsdoc,
+    /// A plain module docstring, without the `# -*- coding` line.
+    PythonDocstring,
+    /// Python `#` line comments, without the `# -*- coding` line.
+    PythonComment,
+    /// Go package docs: `//` comments directly above `package`.
+    GoDoc,
+}
+
+impl NamedProfile {
+    /// Template used when the extension doesn't set its own.
+    fn template(self) -> Option<&'static str> {
+        match self {
+            Self::Jsdoc => Some(
+                "@file {filename}\n@author {author}\n@date {date}\n@copyright {year} {copyright_holder}",
+            ),
+            Self::RustDoc | Self::PythonDocstring | Self::PythonComment | Self::GoDoc => None,
+        }
+    }
+}
+
+/// Banner decoration around the header comment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum BoxStyle {
+    /// Plain comment.
+    #[default]
+    None,
+    /// Rules of `*` above and below, e.g. `/*****` ... ` *****/`.
+    Stars,
+    /// Rules of `=`, e.g. `# =====`.
+    Equals,
+    /// Rules of `-`.
+    Dashes,
+    /// `*` rules plus a right edge aligned with them on every line.
+    Box,
+}
+
+impl BoxStyle {
+    /// Character the rules are drawn with.
+    fn fill(self) -> char {
+        match self {
+            Self::Equals => '=',
+            Self::Dashes => '-',
+            Self::None | Self::Stars | Self::Box => '*',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct ExtensionHeaderConfig {
-    template: String,
+    /// Comment-wrapped header; empty means use `[header] template`, even if
+    /// a lower-priority config sets one for this extension.
+    #[serde(default)]
+    template: Option<String>,
+    /// Code emitted verbatim after the header (include guards, `package`
+    /// declarations, ...). May contain `{cursor}` to place the caret. Empty
+    /// means no body, even if a lower-priority config sets one.
+    #[serde(default)]
+    body: Option<String>,
+    /// Overrides the top-level `enabled` for this extension.
+    #[serde(default)]
+    enabled: Option<bool>,
+    /// Overrides `[header] line_ending` for this extension.
+    #[serde(default)]
+    line_ending: Option<LineEnding>,
+    /// Overrides `[header] bom` for this extension.
+    #[serde(default)]
+    bom: Option<bool>,
+    /// Overrides `[header] wrap_width` for this extension.
+    #[serde(default)]
+    wrap_width: Option<usize>,
+    /// Overrides `[header] box_style` for this extension.
+    #[serde(default)]
+    box_style: Option<BoxStyle>,
+    /// Built-in doc-comment profile to use instead of the extension's
+    /// default comment style: `rust-doc` (`//!`), `jsdoc` (`/** */` with
+    /// `@file`/`@author` tags unless `template` is set), `python-docstring`
+    /// (no coding line), `python-comment` (`#` lines, no coding line) or
+    /// `go-doc` (`//` directly above `package`).
+    #[serde(default)]
+    profile: Option<NamedProfile>,
+    /// Whether to start with `# -*- coding: utf-8 -*-` (below any
+    /// shebang). Defaults to `true` for the built-in Python style and
+    /// `false` everywhere else, including the `python-*` profiles.
+    #[serde(default)]
+    encoding_cookie: Option<bool>,
+    /// `#!` line: a program (`bash`, via `/usr/bin/env`), a program with
+    /// arguments (`deno run -A`, via `env -S`), an absolute path
+    /// (`/bin/sh`), a full `#!...` line, or `none`. Defaults to the
+    /// extension's interpreter for script languages, and for Python files
+    /// under a `bin/` or `scripts/` directory.
+    #[serde(default)]
+    shebang: Option<String>,
+    /// On Unix, mark the file executable (`chmod +x`) after inserting a
+    /// header that starts with a shebang.
+    #[serde(default)]
+    make_executable: Option<bool>,
+}
+
+impl ExtensionHeaderConfig {
+    /// Field-wise merge: whatever `self` sets wins, the rest comes from
+    /// `lower`. Setting `template` or `body` to `""` clears the lower one.
+    fn merge(self, lower: ExtensionHeaderConfig) -> ExtensionHeaderConfig {
+        ExtensionHeaderConfig {
+            template: self.template.or(lower.template),
+            body: self.body.or(lower.body),
+            enabled: self.enabled.or(lower.enabled),
+            line_ending: self.line_ending.or(lower.line_ending),
+            bom: self.bom.or(lower.bom),
+            wrap_width: self.wrap_width.or(lower.wrap_width),
+            box_style: self.box_style.or(lower.box_style),
+            profile: self.profile.or(lower.profile),
+            encoding_cookie: self.encoding_cookie.or(lower.encoding_cookie),
+            shebang: self.shebang.or(lower.shebang),
+            make_executable: self.make_executable.or(lower.make_executable),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 struct Config {
+    /// Master switch; `false` stops header insertion for this scope.
+    #[serde(default = "default_true")]
+    enabled: bool,
+    /// Refuse to insert headers while any config file has errors that
+    /// `validate` reports (unknown keys, placeholders, ...).
+    #[serde(default)]
+    strict: bool,
     author: AuthorConfig,
+    /// Additional co-owners listed by `{authors}`; empty means just `author`.
+    #[serde(default)]
+    authors: Vec<AuthorConfig>,
     project: ProjectConfig,
     header: HeaderConfig,
+    /// Choices and defaults for `{prompt:Label}` placeholders, keyed by label.
+    #[serde(default)]
+    prompts: HashMap<String, PromptConfig>,
+    #[serde(default)]
+    insert: InsertConfig,
+    #[serde(default)]
+    ignore: IgnoreConfig,
+}
+
+/// Which empty files get a header when opened.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct InsertConfig {
+    /// Only files reported through `workspace/didCreateFiles` or
+    /// `workspace/willCreateFiles` during this session, if the client
+    /// reports them.
+    #[serde(default = "default_true")]
+    only_created: bool,
+    /// Skip empty files older than this many seconds; 0 disables the check.
+    #[serde(default)]
+    max_age_secs: u64,
+    /// Skip empty files that are already tracked by git (`__init__.py`,
+    /// `py.typed`, `.gitkeep`, ...).
+    #[serde(default = "default_true")]
+    skip_git_tracked: bool,
+}
+
+impl Default for InsertConfig {
+    fn default() -> Self {
+        Self {
+            only_created: true,
+            max_age_secs: 0,
+            skip_git_tracked: true,
+        }
+    }
+}
+
+/// How to ask for a `{prompt:Label}` value when a header is inserted.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+struct PromptConfig {
+    /// Offered as buttons via `window/showMessageRequest`. Without options
+    /// the value is typed in: the prompt becomes a tab stop for clients
+    /// with snippet edits, and `default` is used for everyone else.
+    #[serde(default)]
+    options: Vec<String>,
+    /// Used when the client cancels or can't show the request, and as the
+    /// tab stop's text for prompts without options (else the label).
+    #[serde(default)]
+    default: String,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            enabled: true,
+            strict: false,
             author: AuthorConfig {
                 name: "Auto Header".to_string(),
                 email: "auto@header.dev".to_string(),
             },
+            authors: Vec::new(),
             project: ProjectConfig {
                 name: "My Project".to_string(),
                 copyright_holder: String::new(),
+                source_roots: package::default_source_roots(),
             },
             header: HeaderConfig {
                 template: r#"File: {filename}
@@ -288,37 +930,156 @@ Author: {author}
 Date: {date}
 Copyright (c) {year} {copyright_holder}"#
                     .to_string(),
+                unresolved: UnresolvedPolicy::default(),
+                line_ending: LineEnding::default(),
+                bom: None,
+                wrap_width: None,
+                box_style: BoxStyle::default(),
                 by_extension: HashMap::new(),
             },
+            prompts: HashMap::new(),
+            insert: InsertConfig::default(),
+            ignore: IgnoreConfig::default(),
         }
     }
 }
 
 /// Partial versions of config structs for merging from multiple sources.
 /// All fields are Option so each config file only needs to specify what it overrides.
-#[derive(Debug, Clone, Deserialize, Default)]
+/// These are also the source of the JSON Schema (see `schema.rs`), so their
+/// doc comments double as hover text in `.auto-header.toml`.
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct PartialAuthorConfig {
+    /// Your name, used by `{author}` and as the default copyright holder.
     name: Option<String>,
+    /// Your email, used by `{email}`.
     email: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct PartialProjectConfig {
+    /// Project name, used by `{project}`.
     name: Option<String>,
+    /// Used by `{copyright_holder}`; defaults to the author name.
     copyright_holder: Option<String>,
+    /// Directories that `{package}`, `{namespace}` and `{module}` are
+    /// relative to. The first one found in the file's path wins.
+    source_roots: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct PartialHeaderConfig {
+    /// Header text without comment markers; it is wrapped in the comment
+    /// syntax of each file's language. Placeholders such as `{filename}`
+    /// or `{authors}` are filled in; there is no `{contributors}`, since a
+    /// new file has no history to take them from.
     template: Option<String>,
+    /// What to do with placeholders that don't resolve, such as `{typo}`:
+    /// `keep` them verbatim (default), `remove` them, or `error` to skip
+    /// the header. All three log and show a warning.
+    unresolved: Option<UnresolvedPolicy>,
+    /// `lf`, `crlf`, or `auto` (default): `.editorconfig` `end_of_line`,
+    /// then the line endings most files in the same directory use.
+    line_ending: Option<LineEnding>,
+    /// Start the file with a UTF-8 byte order mark. Unset follows
+    /// `.editorconfig` `charset = utf-8-bom`.
+    bom: Option<bool>,
+    /// Reflow paragraphs of the rendered template so that lines, comment
+    /// markers included, fit this many columns. Blank lines, indented lines
+    /// and long words such as URLs are kept; 0 turns it off. Without it,
+    /// `.editorconfig` `max_line_length` only breaks overlong lines.
+    wrap_width: Option<usize>,
+    /// Banner decoration: `none` (default), `stars`, `equals` or `dashes`
+    /// rules above and below the header, or a `box` that also closes every
+    /// line with an aligned right edge. Rules are `wrap_width` (or
+    /// `.editorconfig` `max_line_length`, else 80) columns wide.
+    box_style: Option<BoxStyle>,
+    /// Per-extension template, body, on/off switch, encoding, width and
+    /// banner, keyed by file extension without the dot.
     by_extension: Option<HashMap<String, ExtensionHeaderConfig>>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+/// Files that never get a header, checked by `exclude::skip_reason`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct IgnoreConfig {
+    /// Gitignore-style patterns relative to the workspace root.
+    #[serde(default)]
+    patterns: Vec<String>,
+    /// Respect the workspace's `.gitignore` and `.ignore` files.
+    #[serde(default = "default_true")]
+    gitignore: bool,
+    /// Also insert into files that aren't inside any workspace folder.
+    #[serde(default)]
+    outside_workspace: bool,
+}
+
+impl Default for IgnoreConfig {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            gitignore: true,
+            outside_workspace: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+struct PartialInsertConfig {
+    /// Only insert into files the editor reported as created this session
+    /// (default). Editors that don't report file creation fall back to the
+    /// age and git checks.
+    only_created: Option<bool>,
+    /// Skip pre-existing empty files older than this many seconds (0 = off).
+    max_age_secs: Option<u64>,
+    /// Skip empty files that are already tracked by git (default).
+    skip_git_tracked: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+struct PartialIgnoreConfig {
+    /// Gitignore-style patterns, relative to the workspace root, for files
+    /// that never get a header. Patterns from all config files add up.
+    patterns: Option<Vec<String>>,
+    /// Respect `.gitignore` and `.ignore` files (on by default).
+    gitignore: Option<bool>,
+    /// Also insert headers into files outside every workspace folder.
+    outside_workspace: Option<bool>,
+}
+
+/// Configuration for the Auto File Header extension.
+#[derive(Debug, Clone, Deserialize, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct PartialConfig {
+    /// Set to `false` to stop inserting headers in this scope.
+    enabled: Option<bool>,
+    /// Don't insert headers while any config file has unknown keys, unknown
+    /// placeholders or other errors; report them instead. Warnings don't
+    /// block.
+    strict: Option<bool>,
+    /// The author used by `{author}` and `{email}`.
     author: Option<PartialAuthorConfig>,
+    /// Everyone listed by `{authors}`; replaces `[author]` there. A list in
+    /// a higher-priority file replaces the whole list.
+    authors: Option<Vec<AuthorConfig>>,
+    /// Project details used by `{project}` and friends.
     project: Option<PartialProjectConfig>,
+    /// The header template and per-extension overrides.
     header: Option<PartialHeaderConfig>,
+    /// Choices and defaults for `{prompt:Label}` placeholders, keyed by label.
+    prompts: Option<HashMap<String, PromptConfig>>,
+    /// Which empty files get a header when opened.
+    insert: Option<PartialInsertConfig>,
+    /// Files that never get a header.
+    ignore: Option<PartialIgnoreConfig>,
 }
 
 impl PartialConfig {
@@ -326,6 +1087,8 @@ impl PartialConfig {
     /// Fields present in `self` win; missing fields fall back to `lower`.
     fn merge(self, lower: PartialConfig) -> PartialConfig {
         PartialConfig {
+            enabled: self.enabled.or(lower.enabled),
+            strict: self.strict.or(lower.strict),
             author: match (self.author, lower.author) {
                 (Some(high), Some(low)) => Some(PartialAuthorConfig {
                     name: high.name.or(low.name),
@@ -333,27 +1096,72 @@ impl PartialConfig {
                 }),
                 (a, b) => a.or(b),
             },
+            // Author lists are replaced wholesale rather than concatenated
+            authors: self.authors.or(lower.authors),
             project: match (self.project, lower.project) {
                 (Some(high), Some(low)) => Some(PartialProjectConfig {
                     name: high.name.or(low.name),
                     copyright_holder: high.copyright_holder.or(low.copyright_holder),
+                    source_roots: high.source_roots.or(low.source_roots),
                 }),
                 (a, b) => a.or(b),
             },
             header: match (self.header, lower.header) {
                 (Some(high), Some(low)) => Some(PartialHeaderConfig {
                     template: high.template.or(low.template),
+                    unresolved: high.unresolved.or(low.unresolved),
+                    line_ending: high.line_ending.or(low.line_ending),
+                    bom: high.bom.or(low.bom),
+                    wrap_width: high.wrap_width.or(low.wrap_width),
+                    box_style: high.box_style.or(low.box_style),
                     by_extension: Some({
-                        // Start from lower priority map, then override with higher priority keys
+                        // Start from lower priority map, then merge higher priority
+                        // entries over it field by field
                         let mut merged = low.by_extension.unwrap_or_default();
-                        if let Some(ext) = high.by_extension {
-                            merged.extend(ext);
+                        for (ext, high_entry) in high.by_extension.unwrap_or_default() {
+                            let entry = match merged.remove(&ext) {
+                                Some(low_entry) => high_entry.merge(low_entry),
+                                None => high_entry,
+                            };
+                            merged.insert(ext, entry);
                         }
                         merged
                     }),
                 }),
                 (a, b) => a.or(b),
             },
+            prompts: match (self.prompts, lower.prompts) {
+                (Some(high), Some(mut low)) => {
+                    // Prompts are merged per label, like `by_extension`
+                    low.extend(high);
+                    Some(low)
+                }
+                (a, b) => a.or(b),
+            },
+            insert: match (self.insert, lower.insert) {
+                (Some(high), Some(low)) => Some(PartialInsertConfig {
+                    only_created: high.only_created.or(low.only_created),
+                    max_age_secs: high.max_age_secs.or(low.max_age_secs),
+                    skip_git_tracked: high.skip_git_tracked.or(low.skip_git_tracked),
+                }),
+                (a, b) => a.or(b),
+            },
+            ignore: match (self.ignore, lower.ignore) {
+                (Some(high), Some(low)) => Some(PartialIgnoreConfig {
+                    // Patterns accumulate; lower priority first so a
+                    // higher-priority `!pattern` can re-include files
+                    patterns: match (low.patterns, high.patterns) {
+                        (Some(mut low), Some(high)) => {
+                            low.extend(high);
+                            Some(low)
+                        }
+                        (a, b) => a.or(b),
+                    },
+                    gitignore: high.gitignore.or(low.gitignore),
+                    outside_workspace: high.outside_workspace.or(low.outside_workspace),
+                }),
+                (a, b) => a.or(b),
+            },
         }
     }
 
@@ -364,18 +1172,27 @@ impl PartialConfig {
         let project = self.project.unwrap_or_default();
         let header = self.header.unwrap_or_default();
         Config {
+            enabled: self.enabled.unwrap_or(default.enabled),
+            strict: self.strict.unwrap_or(default.strict),
             author: AuthorConfig {
                 name: author.name.unwrap_or(default.author.name),
                 email: author.email.unwrap_or(default.author.email),
             },
+            authors: self.authors.unwrap_or(default.authors),
             project: ProjectConfig {
                 name: project.name.unwrap_or(default.project.name),
                 copyright_holder: project
                     .copyright_holder
                     .unwrap_or(default.project.copyright_holder),
+                source_roots: project.source_roots.unwrap_or(default.project.source_roots),
             },
             header: HeaderConfig {
                 template: header.template.unwrap_or(default.header.template),
+                unresolved: header.unresolved.unwrap_or(default.header.unresolved),
+                line_ending: header.line_ending.unwrap_or(default.header.line_ending),
+                bom: header.bom.or(default.header.bom),
+                wrap_width: header.wrap_width.or(default.header.wrap_width),
+                box_style: header.box_style.unwrap_or(default.header.box_style),
                 by_extension: {
                     // Default extension map first, then overlay whatever was configured
                     let mut merged = default.header.by_extension;
@@ -385,32 +1202,132 @@ impl PartialConfig {
                     merged
                 },
             },
+            prompts: self.prompts.unwrap_or_default(),
+            insert: {
+                let insert = self.insert.unwrap_or_default();
+                InsertConfig {
+                    only_created: insert.only_created.unwrap_or(default.insert.only_created),
+                    max_age_secs: insert.max_age_secs.unwrap_or(default.insert.max_age_secs),
+                    skip_git_tracked: insert
+                        .skip_git_tracked
+                        .unwrap_or(default.insert.skip_git_tracked),
+                }
+            },
+            ignore: {
+                let ignore = self.ignore.unwrap_or_default();
+                IgnoreConfig {
+                    patterns: ignore.patterns.unwrap_or(default.ignore.patterns),
+                    gitignore: ignore.gitignore.unwrap_or(default.ignore.gitignore),
+                    outside_workspace: ignore
+                        .outside_workspace
+                        .unwrap_or(default.ignore.outside_workspace),
+                }
+            },
+        }
+    }
+}
+
+/// A config file found in one of the search locations.
+#[derive(Debug)]
+struct ConfigSource {
+    /// `project`, `home` or `platform`.
+    scope: &'static str,
+    path: PathBuf,
+    /// The parsed file, or why it was ignored.
+    partial: std::result::Result<PartialConfig, String>,
+}
+
+impl ConfigSource {
+    /// `path (scope)`, noting the parse error if the file was ignored.
+    fn describe(&self) -> String {
+        match &self.partial {
+            Ok(_) => format!("{} ({})", self.path.display(), self.scope),
+            Err(e) => format!("{} ({}, ignored: {})", self.path.display(), self.scope, e),
         }
     }
 }
 
 impl Config {
-    /// Check if any config file exists in the search paths
-    /// Takes an optional workspace root directory to check for project-local config
-    fn config_exists(workspace_root: Option<&Path>) -> bool {
+    /// Config file locations in priority order (highest first), whether or
+    /// not they exist.
+    fn search_paths(workspace_root: Option<&Path>) -> Vec<(&'static str, PathBuf)> {
         let mut config_paths = Vec::new();
 
         // 1. Project root (if provided)
         if let Some(root) = workspace_root {
-            config_paths.push(root.join(".auto-header.toml"));
+            config_paths.push(("project", root.join(".auto-header.toml")));
         }
 
         // 2. Home directory: ~/.auto-header.toml
         if let Some(home_dir) = dirs::home_dir() {
-            config_paths.push(home_dir.join(".auto-header.toml"));
+            config_paths.push(("home", home_dir.join(".auto-header.toml")));
         }
 
         // 3. Platform-specific config directory fallback
-        config_paths.push(config_dir().join("auto-header.toml"));
+        config_paths.push(("platform", config_dir().join("auto-header.toml")));
+
+        // 4. Where older versions loaded the platform config from (one
+        //    `zed/` too deep), still read so those configs keep working
+        config_paths.push((
+            "platform, old location",
+            config_dir().join("zed").join("auto-header.toml"),
+        ));
 
         config_paths
+            .into_iter()
+            .filter(|(_, path)| !path.as_os_str().is_empty())
+            .collect()
+    }
+
+    /// Check if any config file exists in the search paths
+    /// Takes an optional workspace root directory to check for project-local config
+    fn config_exists(workspace_root: Option<&Path>) -> bool {
+        Self::search_paths(workspace_root)
+            .iter()
+            .any(|(_, path)| path.exists())
+    }
+
+    /// Every existing config file, highest priority first, parsed.
+    fn sources(workspace_root: Option<&Path>) -> Vec<ConfigSource> {
+        Self::sources_at(Self::search_paths(workspace_root))
+    }
+
+    /// The files among `paths` (scope and path, highest priority first)
+    /// that exist, parsed.
+    fn sources_at(paths: Vec<(&'static str, PathBuf)>) -> Vec<ConfigSource> {
+        paths
+            .into_iter()
+            .filter(|(_, path)| path.exists())
+            .map(|(scope, path)| {
+                let partial = std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|content| {
+                        toml::from_str::<PartialConfig>(&content).map_err(|e| {
+                            let line = e
+                                .span()
+                                .map_or(1, |span| content[..span.start].matches('\n').count() + 1);
+                            format!("line {}: {}", line, e.message())
+                        })
+                    });
+                ConfigSource {
+                    scope,
+                    path,
+                    partial,
+                }
+            })
+            .collect()
+    }
+
+    /// Merge already-loaded sources (highest priority first) over the
+    /// built-in default. Files that failed to parse are skipped.
+    fn from_sources(sources: &[ConfigSource]) -> Self {
+        sources
             .iter()
-            .any(|path| !path.as_os_str().is_empty() && path.exists())
+            .filter_map(|source| source.partial.as_ref().ok())
+            .fold(PartialConfig::default(), |merged, partial| {
+                merged.merge(partial.clone())
+            })
+            .into_config()
     }
 
     /// Load and merge configs from all locations with explicit priority:
@@ -418,61 +1335,466 @@ impl Config {
     ///
     /// Each file is optional and only needs to specify the keys it wants to override.
     fn load_from_workspace(workspace_root: Option<&Path>) -> Self {
-        let try_load = |path: std::path::PathBuf| -> Option<PartialConfig> {
-            if path.as_os_str().is_empty() || !path.exists() {
-                return None;
-            }
-            let content = std::fs::read_to_string(&path).ok()?;
-            toml::from_str::<PartialConfig>(&content).ok()
-        };
-
-        // Platform config directory (lowest priority among file sources)
-        let platform_config =
-            try_load(config_dir().join("zed").join("auto-header.toml")).unwrap_or_default();
-
-        // Home directory (medium priority)
-        let home_config = dirs::home_dir()
-            .and_then(|d| try_load(d.join(".auto-header.toml")))
-            .unwrap_or_default();
-
-        // Project root (highest priority)
-        let project_config = workspace_root
-            .and_then(|r| try_load(r.join(".auto-header.toml")))
-            .unwrap_or_default();
-
-        // Merge: project > home > platform, then fill gaps with built-in default
-        project_config
-            .merge(home_config)
-            .merge(platform_config)
-            .into_config()
+        Self::from_sources(&Self::sources(workspace_root))
     }
 
     fn load() -> Self {
         Self::load_from_workspace(None)
     }
 
-    fn get_template_for_file(&self, file_path: &str) -> String {
-        // Extract file extension
-        let ext = Path::new(file_path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("");
-
-        // Get the header profile for this file type
-        let header_profile = HeaderProfile::for_extension(ext);
-
+    /// Get the raw (not yet comment-wrapped) template for a file extension.
+    fn template_for_extension(&self, ext: &str) -> &str {
         // Priority 1: Check if user has custom template for this specific extension
         if let Some(ext_config) = self.header.by_extension.get(ext) {
-            // Wrap user's extension-specific template with appropriate comment syntax
-            return header_profile.wrap(&ext_config.template);
+            if let Some(template) = ext_config.template.as_deref().filter(|t| !t.is_empty()) {
+                return template;
+            }
+            // Priority 1b: The named profile's own template (jsdoc tags)
+            if let Some(template) = ext_config.profile.and_then(NamedProfile::template) {
+                return template;
+            }
         }
 
         // Priority 2: Use user's default template from [header] section
-        // Always wrap with appropriate comment syntax
-        header_profile.wrap(&self.header.template)
+        &self.header.template
+    }
+
+    /// The comment profile for `ext`: its named `profile` if configured,
+    /// otherwise the built-in one for the extension, with the extension's
+    /// `encoding_cookie` applied.
+    fn profile_for(&self, ext: &str) -> HeaderProfile {
+        let ext_config = self.header.by_extension.get(ext);
+        let mut profile = match ext_config.and_then(|c| c.profile) {
+            Some(name) => HeaderProfile::named(name),
+            None => HeaderProfile::for_extension(ext),
+        };
+        if let Some(cookie) = ext_config.and_then(|c| c.encoding_cookie) {
+            profile.coding_cookie = cookie;
+        }
+        profile
+    }
+
+    /// The `#!` line for `file`: its extension's `shebang` setting if there
+    /// is one, otherwise `#!/usr/bin/env <interpreter>` for script profiles
+    /// and for files with an interpreter in a `bin/` or `scripts/`
+    /// directory of the workspace.
+    fn shebang_for(&self, file: &Path, workspace_root: Option<&Path>) -> Option<String> {
+        let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match self
+            .header
+            .by_extension
+            .get(ext)
+            .and_then(|c| c.shebang.as_deref())
+        {
+            Some(spec) => shebang::line(spec),
+            None if self.profile_for(ext).script
+                || shebang::in_script_dir(file, workspace_root) =>
+            {
+                shebang::line(shebang::default_interpreter(ext))
+            }
+            None => None,
+        }
+    }
+
+    /// Whether inserting a header into `file` should make it executable:
+    /// its extension sets `make_executable` and the header has a shebang.
+    fn make_executable_for(&self, file: &Path, workspace_root: Option<&Path>) -> bool {
+        let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+        self.header
+            .by_extension
+            .get(ext)
+            .and_then(|c| c.make_executable)
+            .unwrap_or(false)
+            && self.shebang_for(file, workspace_root).is_some()
+    }
+
+    /// A copy whose `shebang` for `ext` is `line`, for files that already
+    /// start with one.
+    fn with_shebang(&self, ext: &str, line: &str) -> Config {
+        let mut config = self.clone();
+        let ext_config = config
+            .header
+            .by_extension
+            .entry(ext.to_string())
+            .or_default();
+        ext_config.shebang = Some(line.to_string());
+        config
+    }
+
+    /// Whether headers should be inserted for `ext`: the extension's own
+    /// `enabled` if set, otherwise the top-level switch.
+    fn is_enabled_for(&self, ext: &str) -> bool {
+        self.header
+            .by_extension
+            .get(ext)
+            .and_then(|ext_config| ext_config.enabled)
+            .unwrap_or(self.enabled)
+    }
+
+    /// Line endings and BOM for `file`, per-extension settings first.
+    fn encoding_for(&self, file: &Path) -> encoding::Encoding {
+        let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let ext_config = self.header.by_extension.get(ext);
+        encoding::Encoding::resolve(
+            file,
+            ext_config
+                .and_then(|c| c.line_ending)
+                .unwrap_or(self.header.line_ending),
+            ext_config.and_then(|c| c.bom).or(self.header.bom),
+        )
+    }
+
+    /// `.editorconfig` layout for `file`, with `wrap_width` (per-extension
+    /// first) turning on paragraph reflow at that width, and the banner.
+    fn layout_for(&self, file: &Path) -> Layout {
+        let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let ext_config = self.header.by_extension.get(ext);
+        let wrap_width = ext_config
+            .and_then(|c| c.wrap_width)
+            .or(self.header.wrap_width);
+        let mut layout = Layout::for_file(file);
+        layout.box_style = ext_config
+            .and_then(|c| c.box_style)
+            .unwrap_or(self.header.box_style);
+        if let Some(width) = wrap_width.filter(|w| *w > 0) {
+            layout.max_line_length = Some(width);
+            layout.reflow = true;
+        }
+        layout
+    }
+
+    /// Get the raw body template emitted after the header, if any.
+    fn body_for_extension(&self, ext: &str) -> Option<&str> {
+        self.header
+            .by_extension
+            .get(ext)
+            .and_then(|ext_config| ext_config.body.as_deref())
+            .filter(|body| !body.is_empty())
+    }
+
+    /// Labels of every `{prompt:Label}` in the template and body for `ext`.
+    fn prompt_labels(&self, ext: &str) -> Vec<&str> {
+        let mut labels = template::prompts(self.template_for_extension(ext));
+        if let Some(body) = self.body_for_extension(ext) {
+            for label in template::prompts(body) {
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+        }
+        labels
+    }
+
+    /// Tab stops for the `{prompt:Label}`s in the template and body for
+    /// `ext` that have no options, numbered after the template's own, so
+    /// their text is typed in. Each holds the prompt's default, or else the
+    /// label.
+    fn free_text_prompts(&self, ext: &str) -> HashMap<String, String> {
+        let templates = [self.template_for_extension(ext)]
+            .into_iter()
+            .chain(self.body_for_extension(ext));
+        let mut index = templates.map(snippet::max_index).max().unwrap_or(0);
+        let mut answers = HashMap::new();
+        for label in self.prompt_labels(ext) {
+            let prompt = self.prompts.get(label);
+            if prompt.is_some_and(|p| !p.options.is_empty()) {
+                continue;
+            }
+            let default = prompt
+                .map(|p| p.default.as_str())
+                .filter(|d| !d.is_empty())
+                .unwrap_or(label);
+            index += 1;
+            answers.insert(label.to_string(), snippet::tab_stop(index, default));
+        }
+        answers
+    }
+
+    /// Resolve `{env:VAR}` / `{env:VAR:-fallback}` in author and project
+    /// strings against the current process environment. Templates get the
+    /// same treatment from `template::render` when the header is generated.
+    fn resolve_env(mut self) -> Self {
+        let no_vars = template::Vars::new();
+        let expand = |s: &mut String| *s = template::render(s, &no_vars);
+        for author in std::iter::once(&mut self.author).chain(self.authors.iter_mut()) {
+            expand(&mut author.name);
+            expand(&mut author.email);
+        }
+        expand(&mut self.project.name);
+        expand(&mut self.project.copyright_holder);
+        self
+    }
+
+    /// Everyone listed by `{authors}`: the `[[authors]]` array, or the single
+    /// `[author]` when no array is configured.
+    fn author_lines(&self) -> Vec<String> {
+        if self.authors.is_empty() {
+            vec![self.author.display()]
+        } else {
+            self.authors.iter().map(AuthorConfig::display).collect()
+        }
+    }
+
+    /// Substitute all template variables and wrap the result in the comment
+    /// syntax for the file's extension, followed by the raw body if one is
+    /// configured. `{cursor}` becomes `snippet::CURSOR_MARKER` for `Header::new`.
+    /// `{prompt:Label}` values come from `answers` (keyed by label) or else
+    /// the configured prompt default. Substituted values are plain text, so
+    /// a `${1:x}` in a path or answer never becomes a tab stop; only the
+    /// answers `free_text_prompts` made for `ext` are kept as snippets.
+    ///
+    /// Also returns the placeholders that didn't resolve; they are kept or
+    /// removed according to `[header] unresolved`.
+    fn render_header(
+        &self,
+        file_path: &str,
+        workspace_root: Option<&Path>,
+        answers: &HashMap<String, String>,
+    ) -> (String, Vec<String>) {
+        let ext = Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let template = self.template_for_extension(ext);
+        let body = self.body_for_extension(ext);
+        let mut vars: template::Vars =
+            self.template_vars(file_path, workspace_root, &[template, body.unwrap_or("")]);
+
+        let labels = self.prompt_labels(ext);
+        let keys: Vec<String> = labels.iter().map(|l| template::prompt_key(l)).collect();
+        let tab_stops = self.free_text_prompts(ext);
+        let mut snippet_keys = Vec::new();
+        for (label, key) in labels.iter().zip(&keys) {
+            let answer = answers.get(*label);
+            if answer.is_some() && answer == tab_stops.get(*label) {
+                snippet_keys.push(key.as_str());
+            }
+            let value = answers
+                .get(*label)
+                .cloned()
+                .or_else(|| self.prompts.get(*label).map(|p| p.default.clone()))
+                .unwrap_or_default();
+            vars.insert(key, value);
+        }
+        // `{cursor}` renders to a marker for `Header::new`; an escaped
+        // `{{cursor}}` stays plain text
+        vars.insert("cursor", snippet::CURSOR_MARKER.to_string());
+
+        let remove = self.header.unresolved == UnresolvedPolicy::Remove;
+        let escape = |name: &str, value: String| match snippet_keys.contains(&name) {
+            true => value,
+            false => snippet::literal(&value),
+        };
+        let (content, mut unresolved) =
+            template::render_reporting(template, &vars, remove, &escape);
+        // Wrap the substituted content with the language's comment syntax
+        let layout = self.layout_for(Path::new(file_path));
+        let mut header = self
+            .shebang_for(Path::new(file_path), workspace_root)
+            .map(|line| line + "\n")
+            .unwrap_or_default();
+        header.push_str(&self.profile_for(ext).wrap(&content, &layout));
+        if let Some(body) = body {
+            let (body, body_unresolved) = template::render_reporting(body, &vars, remove, &escape);
+            header.push_str(&body);
+            for placeholder in body_unresolved {
+                if !unresolved.contains(&placeholder) {
+                    unresolved.push(placeholder);
+                }
+            }
+        }
+        (header, unresolved)
+    }
+
+    /// Compute every template variable for `file_path`. Lookups that shell
+    /// out or read extra files only run if one of `templates` uses them.
+    fn template_vars(
+        &self,
+        file_path: &str,
+        workspace_root: Option<&Path>,
+        templates: &[&str],
+    ) -> template::Vars<'static> {
+        let uses = |name: &str| templates.iter().any(|t| template::uses(t, name));
+        let now = Local::now();
+        let date = now.format("%Y-%m-%d").to_string();
+        let time = now.format("%H:%M:%S").to_string();
+        let year = now.format("%Y").to_string();
+
+        // Extract filename and extension from path
+        let path = Path::new(file_path);
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+        // Determine copyright holder
+        let copyright_holder = if self.project.copyright_holder.is_empty() {
+            &self.author.name
+        } else {
+            &self.project.copyright_holder
+        };
+
+        // The shebang's program, or the extension's usual one without a shebang
+        let shebang = self.shebang_for(path, workspace_root);
+        let interpreter = shebang
+            .as_deref()
+            .and_then(shebang::interpreter)
+            .unwrap_or(shebang::default_interpreter(ext));
+
+        // Owning teams/users from CODEOWNERS, relative to the workspace root;
+        // a file outside the root has none
+        let relative = workspace_root.and_then(|root| Some((root, path.strip_prefix(root).ok()?)));
+        let owners = match relative {
+            Some((root, relative)) if uses("owners") => codeowners::CodeOwners::load(root)
+                .map(|c| c.owners_of(relative).join(" "))
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+
+        // Package/namespace/module names derived from the directory layout
+        let roots = &self.project.source_roots;
+        let derived = |name: &str, derive: fn(&Path, Option<&Path>, &[String]) -> String| {
+            if uses(name) {
+                derive(path, workspace_root, roots)
+            } else {
+                String::new()
+            }
+        };
+        let package = derived("package", package::package);
+        let namespace = derived("namespace", package::namespace);
+        let module = derived("module", package::module);
+
+        HashMap::from([
+            ("filename", filename.to_string()),
+            ("filepath", file_path.to_string()),
+            ("date", date),
+            ("time", time),
+            ("year", year),
+            ("author", self.author.name.clone()),
+            ("email", self.author.email.clone()),
+            ("authors", self.author_lines().join("\n")),
+            ("project", self.project.name.clone()),
+            ("copyright_holder", copyright_holder.clone()),
+            ("interpreter", interpreter.to_string()),
+            ("owners", owners),
+            ("package", package),
+            ("namespace", namespace),
+            ("module", module),
+        ])
+    }
+}
+
+/// Every variable `Config::template_vars` provides, with a short description
+/// for completion and hover in `.auto-header.toml`.
+const TEMPLATE_VARIABLES: &[(&str, &str)] = &[
+    ("filename", "Name of the file, e.g. `example.rs`"),
+    ("filepath", "Full path of the file"),
+    ("date", "Current date, `YYYY-MM-DD`"),
+    ("time", "Current time, `HH:MM:SS`"),
+    ("year", "Current year"),
+    ("author", "`[author] name`"),
+    ("email", "`[author] email`"),
+    (
+        "authors",
+        "Every `[[authors]]` entry, one per line (defaults to `[author]`)",
+    ),
+    ("project", "`[project] name`"),
+    (
+        "copyright_holder",
+        "`[project] copyright_holder`, or the author name",
+    ),
+    (
+        "interpreter",
+        "Program the shebang runs, or the extension's usual interpreter (python3, sh, ...)",
+    ),
+    ("owners", "Owners of the file according to CODEOWNERS"),
+    ("package", "Package from the directory below a source root"),
+    (
+        "namespace",
+        "Namespace (PHP via composer.json PSR-4, C++ as `a::b`)",
+    ),
+    (
+        "module",
+        "Go import path, Python dotted module, or the file stem",
+    ),
+];
+
+/// A header ready to insert, with `{cursor}` and `${N:...}` tab stops resolved.
+#[derive(Debug, PartialEq)]
+struct Header {
+    /// Plain text, with tab stops replaced by their defaults.
+    text: String,
+    /// Where to put the caret after a plain-text insert: the `{cursor}`
+    /// marker, or else the default text of the first tab stop, in LSP
+    /// (UTF-16) coordinates.
+    selection: Option<Range>,
+    /// Snippet syntax for clients that accept snippet edits; `None` when the
+    /// template has no tab stops.
+    snippet: Option<String>,
+}
+
+impl Header {
+    fn new(rendered: String) -> Self {
+        let segments = snippet::parse(&rendered);
+
+        let mut text = String::new();
+        let mut cursor = None;
+        let mut first_tab_stop: Option<(u32, Range)> = None;
+        for segment in &segments {
+            match segment {
+                snippet::Segment::Text(t) => text.push_str(t),
+                snippet::Segment::TabStop { index, default } => {
+                    let start = position_at_end(&text);
+                    text.push_str(default);
+                    let range = Range {
+                        start,
+                        end: position_at_end(&text),
+                    };
+                    // `$0` is the final stop, so any numbered stop comes first
+                    let order = if *index == 0 { u32::MAX } else { *index };
+                    if first_tab_stop.is_none_or(|(best, _)| order < best) {
+                        first_tab_stop = Some((order, range));
+                    }
+                }
+                snippet::Segment::Cursor => {
+                    cursor.get_or_insert_with(|| {
+                        let at = position_at_end(&text);
+                        Range { start: at, end: at }
+                    });
+                }
+            }
+        }
+
+        Self {
+            text,
+            selection: cursor.or(first_tab_stop.map(|(_, range)| range)),
+            snippet: snippet::has_tab_stops(&segments).then(|| snippet::to_snippet(&segments)),
+        }
+    }
+}
+
+/// LSP position just past the end of `text`.
+fn position_at_end(text: &str) -> Position {
+    let line_start = text.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: text.matches('\n').count() as u32,
+        character: text[line_start..].encode_utf16().count() as u32,
     }
 }
 
+/// `workspace/applyEdit` carrying snippet text edits (`insertTextFormat: 2`),
+/// the rust-analyzer extension advertised by clients as
+/// `experimental.snippetTextEdit`. The typed `WorkspaceEdit` can't express it.
+enum ApplySnippetEdit {}
+
+impl request::Request for ApplySnippetEdit {
+    type Params = serde_json::Value;
+    type Result = ApplyWorkspaceEditResponse;
+    const METHOD: &'static str = "workspace/applyEdit";
+}
+
 #[derive(Debug)]
 struct AutoHeaderServer {
     client: Client,
@@ -480,6 +1802,15 @@ struct AutoHeaderServer {
     _initial_config: Config,
     // Store workspace root(s) from initialization
     workspace_folders: std::sync::Arc<tokio::sync::RwLock<Vec<std::path::PathBuf>>>,
+    // Whether the client accepts snippet text edits (`experimental.snippetTextEdit`)
+    snippet_text_edit: std::sync::atomic::AtomicBool,
+    // Files the client reported as created this session, consumed by `did_open`
+    created_files: std::sync::Arc<tokio::sync::RwLock<created::CreatedFiles>>,
+    /// Whether the client sends `workspace/didCreateFiles` or
+    /// `workspace/willCreateFiles`, which `[insert] only_created` needs.
+    reports_creations: std::sync::atomic::AtomicBool,
+    // Open `.auto-header.toml` buffers, for completion and hover
+    config_documents: std::sync::Arc<tokio::sync::RwLock<HashMap<Url, String>>>,
 }
 
 impl AutoHeaderServer {
@@ -489,64 +1820,301 @@ impl AutoHeaderServer {
             client,
             _initial_config: initial_config,
             workspace_folders: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            snippet_text_edit: std::sync::atomic::AtomicBool::new(false),
+            created_files: Default::default(),
+            reports_creations: std::sync::atomic::AtomicBool::new(false),
+            config_documents: Default::default(),
         }
     }
 
-    fn generate_header(&self, file_path: &str, workspace_root: Option<&Path>) -> String {
-        // Reload config each time to pick up changes to .auto-header.toml without restarting Zed
-        let config = Config::load_from_workspace(workspace_root);
-        let now = Local::now();
-        let date = now.format("%Y-%m-%d").to_string();
-        let time = now.format("%H:%M:%S").to_string();
-        let year = now.format("%Y").to_string();
+    /// Insert `header` at the top of the document, replacing everything up
+    /// to `end` (a lone shebang the header repeats): as a snippet when the
+    /// client supports snippet edits and the header has tab stops, otherwise
+    /// as plain text followed by moving the caret to `header.selection`.
+    /// Returns whether the edit was applied.
+    async fn apply_header(&self, uri: &Url, version: i32, header: Header, end: Position) -> bool {
+        let start = Position {
+            line: 0,
+            character: 0,
+        };
+        let range = Range { start, end };
+
+        let snippet_support = self
+            .snippet_text_edit
+            .load(std::sync::atomic::Ordering::Relaxed);
+        let result = match header.snippet {
+            Some(snippet) if snippet_support => {
+                let params = serde_json::json!({
+                    "label": "Insert file header",
+                    "edit": {
+                        "documentChanges": [{
+                            "textDocument": { "uri": uri, "version": version },
+                            "edits": [{
+                                "range": range,
+                                "newText": snippet,
+                                "insertTextFormat": InsertTextFormat::SNIPPET,
+                            }],
+                        }],
+                    },
+                });
+                self.client
+                    .send_request::<ApplySnippetEdit>(params)
+                    .await
+                    .map(|response| (response, false))
+            }
+            _ => {
+                // Create a text edit to insert the header at the beginning
+                let edit = TextEdit {
+                    range,
+                    new_text: header.text,
+                };
+
+                // Apply the workspace edit
+                let mut changes = std::collections::HashMap::new();
+                changes.insert(uri.clone(), vec![edit]);
+
+                let workspace_edit = WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                };
 
-        // Extract filename and extension from path
-        let path = Path::new(file_path);
-        let filename = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
+                self.client
+                    .apply_edit(workspace_edit)
+                    .await
+                    .map(|response| (response, true))
+            }
+        };
 
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        // Send the edit to the client
+        let needs_selection = match result {
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Failed to apply header edit: {:?}", e),
+                    )
+                    .await;
+                return false;
+            }
+            Ok((response, _)) if !response.applied => {
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!(
+                            "[Auto Header] Client rejected the header edit for {}: {}",
+                            uri.path(),
+                            response
+                                .failure_reason
+                                .as_deref()
+                                .unwrap_or("no reason given")
+                        ),
+                    )
+                    .await;
+                return false;
+            }
+            Ok((_, plain)) => {
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!("Header inserted for {}", uri.path()),
+                    )
+                    .await;
+                plain
+            }
+        };
+
+        // Move the caret to `{cursor}` or the first tab stop; snippet
+        // clients already do this themselves
+        if let Some(selection) = header.selection.filter(|_| needs_selection) {
+            let params = ShowDocumentParams {
+                uri: uri.clone(),
+                external: None,
+                take_focus: Some(true),
+                selection: Some(selection),
+            };
+            if let Err(e) = self.client.show_document(params).await {
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("[Auto Header] Failed to place cursor: {:?}", e),
+                    )
+                    .await;
+            }
+        }
+        true
+    }
 
-        // Get the appropriate template for this file type
-        let mut template = config.get_template_for_file(file_path);
+    /// Why an empty file shouldn't get a header under the `[insert]` rules,
+    /// or `None` if it should. Consumes the file's "created" mark.
+    async fn insert_skip_reason(
+        &self,
+        insert: &InsertConfig,
+        file_path: &Path,
+    ) -> Option<&'static str> {
+        let created = self
+            .created_files
+            .write()
+            .await
+            .take(file_path, std::time::Instant::now());
+        let reports_creations = self
+            .reports_creations
+            .load(std::sync::atomic::Ordering::Relaxed);
+        let (insert, file_path) = (insert.clone(), file_path.to_path_buf());
+        blocking(move || created::skip_reason(&insert, &file_path, created, reports_creations))
+            .await
+            .flatten()
+    }
 
-        // Determine copyright holder
-        let copyright_holder = if config.project.copyright_holder.is_empty() {
-            &config.author.name
-        } else {
-            &config.project.copyright_holder
+    /// The workspace folder containing `file_path`, if any.
+    async fn workspace_root_for(&self, file_path: &Path) -> Option<PathBuf> {
+        self.workspace_folders
+            .read()
+            .await
+            .iter()
+            .find(|folder| file_path.starts_with(folder))
+            .cloned()
+    }
+
+    /// Remember files the client is creating so `did_open` can tell new files
+    /// from pre-existing empty ones.
+    async fn record_created(&self, files: Vec<FileCreate>) {
+        let mut created = self.created_files.write().await;
+        let now = std::time::Instant::now();
+        for file in files {
+            let path = Url::parse(&file.uri)
+                .ok()
+                .and_then(|uri| uri.to_file_path().ok());
+            if let Some(path) = path {
+                created.record(path, now);
+            }
+        }
+    }
+
+    /// Render the header for `file_path`, warning about placeholders that
+    /// didn't resolve. `None` if `[header] unresolved = "error"` stopped it.
+    async fn generate_header(
+        &self,
+        config: &Config,
+        file_path: &str,
+        workspace_root: Option<&Path>,
+    ) -> Option<Header> {
+        let answers = self.ask_prompts(config, file_path).await;
+        let (text, unresolved) = {
+            let (config, path) = (config.clone(), file_path.to_string());
+            let root = workspace_root.map(Path::to_path_buf);
+            blocking(move || config.render_header(&path, root.as_deref(), &answers)).await?
         };
+        let text = config.encoding_for(Path::new(file_path)).apply(&text);
+        if unresolved.is_empty() {
+            return Some(Header::new(text));
+        }
 
-        // Determine interpreter for shebang
-        let interpreter = match ext {
-            "py" | "pyw" | "pyx" => "python3",
-            "rb" => "ruby",
-            "pl" | "pm" => "perl",
-            "sh" => "sh",
-            "bash" => "bash",
-            "zsh" => "zsh",
-            "fish" => "fish",
-            "r" | "R" => "Rscript",
-            "jl" => "julia",
-            _ => "",
+        let filename = Path::new(file_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(file_path);
+        let list = unresolved.join(", ");
+        self.client
+            .log_message(
+                MessageType::WARNING,
+                format!(
+                    "[Auto Header] Unresolved placeholders in header for {}: {}",
+                    file_path, list
+                ),
+            )
+            .await;
+        let (kind, message) = match config.header.unresolved {
+            UnresolvedPolicy::Error => (
+                MessageType::ERROR,
+                format!(
+                    "[Auto Header] Header not inserted into {}: unresolved {}",
+                    filename, list
+                ),
+            ),
+            UnresolvedPolicy::Remove => (
+                MessageType::WARNING,
+                format!(
+                    "[Auto Header] Removed unresolved {} from {}",
+                    list, filename
+                ),
+            ),
+            UnresolvedPolicy::Keep => (
+                MessageType::WARNING,
+                format!("[Auto Header] Unresolved {} left in {}", list, filename),
+            ),
         };
+        self.client.show_message(kind, message).await;
+
+        (config.header.unresolved != UnresolvedPolicy::Error).then(|| Header::new(text))
+    }
 
-        // Replace all variables in the template
-        template = template
-            .replace("{filename}", filename)
-            .replace("{filepath}", file_path)
-            .replace("{date}", &date)
-            .replace("{time}", &time)
-            .replace("{year}", &year)
-            .replace("{author}", &config.author.name)
-            .replace("{email}", &config.author.email)
-            .replace("{project}", &config.project.name)
-            .replace("{copyright_holder}", copyright_holder)
-            .replace("{interpreter}", interpreter);
-
-        template
+    /// Ask the user for every `{prompt:Label}` that has configured options;
+    /// the others become tab stops when the client supports snippet edits.
+    /// Labels missing from the result fall back to their default.
+    async fn ask_prompts(&self, config: &Config, file_path: &str) -> HashMap<String, String> {
+        let path = Path::new(file_path);
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+        let free_text = config.free_text_prompts(ext);
+        let mut answers = if self
+            .snippet_text_edit
+            .load(std::sync::atomic::Ordering::Relaxed)
+        {
+            free_text
+        } else {
+            for label in free_text.keys() {
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!(
+                            "[Auto Header] Prompt '{}' has no options and the client can't edit snippets, using its default",
+                            label
+                        ),
+                    )
+                    .await;
+            }
+            HashMap::new()
+        };
+        for label in config.prompt_labels(ext) {
+            let Some(prompt) = config.prompts.get(label).filter(|p| !p.options.is_empty()) else {
+                continue;
+            };
+            let actions = prompt
+                .options
+                .iter()
+                .map(|option| MessageActionItem {
+                    title: option.clone(),
+                    properties: HashMap::new(),
+                })
+                .collect();
+            let response = self
+                .client
+                .show_message_request(
+                    MessageType::INFO,
+                    format!("[Auto Header] {} for {}", label, filename),
+                    Some(actions),
+                )
+                .await;
+            match response {
+                Ok(Some(choice)) => {
+                    answers.insert(label.to_string(), choice.title);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    self.client
+                        .log_message(
+                            MessageType::WARNING,
+                            format!(
+                                "[Auto Header] Prompt '{}' failed, using default: {:?}",
+                                label, e
+                            ),
+                        )
+                        .await;
+                }
+            }
+        }
+        answers
     }
 }
 
@@ -568,11 +2136,61 @@ impl LanguageServer for AutoHeaderServer {
             }
         }
 
+        let snippet_text_edit = params
+            .capabilities
+            .experimental
+            .as_ref()
+            .and_then(|experimental| experimental.get("snippetTextEdit"))
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        self.snippet_text_edit
+            .store(snippet_text_edit, std::sync::atomic::Ordering::Relaxed);
+
+        let reports_creations = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.file_operations.as_ref())
+            .is_some_and(|ops| ops.did_create == Some(true) || ops.will_create == Some(true));
+        self.reports_creations
+            .store(reports_creations, std::sync::atomic::Ordering::Relaxed);
+
+        // Track file creation so headers can be limited to genuinely new files
+        let all_files = FileOperationRegistrationOptions {
+            filters: vec![FileOperationFilter {
+                scheme: Some("file".to_string()),
+                pattern: FileOperationPattern {
+                    glob: "**/*".to_string(),
+                    matches: Some(FileOperationPatternKind::File),
+                    options: None,
+                },
+            }],
+        };
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::FULL,
                 )),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                        did_create: Some(all_files.clone()),
+                        will_create: Some(all_files),
+                        ..Default::default()
+                    }),
+                }),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(
+                        ["[", ".", "{", "|"].iter().map(|c| c.to_string()).collect(),
+                    ),
+                    ..Default::default()
+                }),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![PREVIEW_COMMAND.to_string(), EXPLAIN_COMMAND.to_string()],
+                    ..Default::default()
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -592,10 +2210,119 @@ impl LanguageServer for AutoHeaderServer {
         Ok(())
     }
 
+    async fn will_create_files(&self, params: CreateFilesParams) -> Result<Option<WorkspaceEdit>> {
+        self.record_created(params.files).await;
+        Ok(None)
+    }
+
+    async fn did_create_files(&self, params: CreateFilesParams) {
+        self.record_created(params.files).await;
+    }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        let target = match params.arguments.first() {
+            Some(arg) => {
+                let target = arg.as_str().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("expected a file URI or path")
+                })?;
+                Some(path_argument(target)?)
+            }
+            None => None,
+        };
+
+        let (title, report, json) = match params.command.as_str() {
+            PREVIEW_COMMAND => {
+                let file_path = target.ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("expected a file URI or path")
+                })?;
+                let workspace_root = self.workspace_root_for(&file_path).await;
+                let path = file_path.clone();
+                let preview = blocking(move || preview::preview(&path, workspace_root.as_deref()))
+                    .await
+                    .ok_or_else(tower_lsp::jsonrpc::Error::internal_error)?;
+                let title = format!("Preview for {}", file_path.display());
+                (title, preview.to_string(), serde_json::to_value(preview))
+            }
+            EXPLAIN_COMMAND => {
+                let workspace_root = match target {
+                    Some(path) => self.workspace_root_for(&path).await,
+                    None => self.workspace_folders.read().await.first().cloned(),
+                };
+                let explanation = blocking(move || explain::explain(workspace_root.as_deref()))
+                    .await
+                    .ok_or_else(tower_lsp::jsonrpc::Error::internal_error)?;
+                let title = "Resolved config".to_string();
+                (
+                    title,
+                    explanation.to_string(),
+                    serde_json::to_value(explanation),
+                )
+            }
+            _ => return Err(tower_lsp::jsonrpc::Error::method_not_found()),
+        };
+
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("[Auto Header] {}:\n{}", title, report),
+            )
+            .await;
+        Ok(json.ok())
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let mut documents = self.config_documents.write().await;
+        if let Some(text) = documents.get_mut(&params.text_document.uri) {
+            // Full sync: the last change holds the whole document
+            if let Some(change) = params.content_changes.into_iter().last() {
+                *text = change.text;
+            }
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        self.config_documents
+            .write()
+            .await
+            .remove(&params.text_document.uri);
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        let documents = self.config_documents.read().await;
+        let Some(text) = documents.get(&position.text_document.uri) else {
+            return Ok(None);
+        };
+        let items = schema::completions(text, position.position);
+        Ok((!items.is_empty()).then_some(CompletionResponse::Array(items)))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params;
+        let documents = self.config_documents.read().await;
+        Ok(documents
+            .get(&position.text_document.uri)
+            .and_then(|text| schema::hover(text, position.position)))
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri;
+        let version = params.text_document.version;
         let content = params.text_document.text;
 
+        if uri
+            .to_file_path()
+            .is_ok_and(|path| schema::is_config_file(&path))
+        {
+            self.config_documents
+                .write()
+                .await
+                .insert(uri.clone(), content.clone());
+        }
+
         // Log file opening
         self.client
             .log_message(
@@ -625,189 +2352,1177 @@ impl LanguageServer for AutoHeaderServer {
             }
         };
 
-        let file_path_str = file_path.to_str().unwrap_or("");
+        let file_path_str = file_path.to_str().unwrap_or("");
+
+        // Find the workspace root by checking which workspace folder contains this file
+        let workspace_root = self.workspace_root_for(&file_path).await;
+        let workspace_root = workspace_root.as_deref();
+
+        // Only insert header if:
+        // 1. File is completely empty, or only has a shebang line
+        // 2. A .auto-header.toml config file exists in search paths
+        let config_exists = Config::config_exists(workspace_root);
+        let existing_shebang = shebang::existing(&content);
+
+        if !content.trim().is_empty() && existing_shebang.is_none() {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("[Auto Header] Skipping non-empty file: {}", uri.path()),
+                )
+                .await;
+            return;
+        }
+
+        if !config_exists {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!(
+                        "[Auto Header] No .auto-header.toml found, skipping: {}",
+                        uri.path()
+                    ),
+                )
+                .await;
+            return;
+        }
+
+        // Reload config each time to pick up changes to .auto-header.toml without restarting Zed
+        let root = workspace_root.map(Path::to_path_buf);
+        let Some((sources, config, problems)) = blocking(move || {
+            let sources = Config::sources(root.as_deref());
+            let config = Config::from_sources(&sources).resolve_env();
+            let problems = match config.strict {
+                true => validate::validate_sources(&sources),
+                false => Vec::new(),
+            };
+            (sources, config, problems)
+        })
+        .await
+        else {
+            return;
+        };
+
+        // A file that doesn't parse is left out entirely, `strict` included
+        for source in sources.iter().filter(|source| source.partial.is_err()) {
+            self.client
+                .show_message(
+                    MessageType::ERROR,
+                    format!("[Auto Header] Config ignored: {}", source.describe()),
+                )
+                .await;
+        }
+
+        if config.strict {
+            // Only errors block; warnings are advisory
+            let errors: Vec<_> = problems.iter().filter(|p| p.is_error()).collect();
+            if let Some(first) = errors.first() {
+                for problem in &problems {
+                    let kind = if problem.is_error() {
+                        MessageType::ERROR
+                    } else {
+                        MessageType::WARNING
+                    };
+                    self.client
+                        .log_message(kind, format!("[Auto Header] {}", problem))
+                        .await;
+                }
+                self.client
+                    .show_message(
+                        MessageType::ERROR,
+                        format!(
+                            "[Auto Header] strict = true and the config has {} error(s), header not inserted. First: {}",
+                            errors.len(),
+                            first
+                        ),
+                    )
+                    .await;
+                return;
+            }
+        }
+
+        let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if !config.is_enabled_for(ext) {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("[Auto Header] Disabled by config, skipping: {}", uri.path()),
+                )
+                .await;
+            return;
+        }
+
+        let excluded = {
+            let (ignore, file) = (config.ignore.clone(), file_path.clone());
+            let root = workspace_root.map(Path::to_path_buf);
+            blocking(move || exclude::skip_reason(&ignore, &file, root.as_deref())).await
+        };
+        if let Some(reason) = excluded.flatten() {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("[Auto Header] Skipping {}: {}", uri.path(), reason),
+                )
+                .await;
+            return;
+        }
+
+        if let Some(reason) = self.insert_skip_reason(&config.insert, &file_path).await {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("[Auto Header] Skipping {}: {}", uri.path(), reason),
+                )
+                .await;
+            return;
+        }
+
+        // All conditions met, insert header
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("[Auto Header] Inserting header for: {}", uri.path()),
+            )
+            .await;
+
+        // Keep a shebang the user already wrote; the header replaces it with
+        // the same line followed by the comment
+        let config = match existing_shebang {
+            Some(line) => config.with_shebang(ext, line),
+            None => config,
+        };
+        let Some(header) = self
+            .generate_header(&config, file_path_str, workspace_root)
+            .await
+        else {
+            return;
+        };
+
+        let applied = self
+            .apply_header(&uri, version, header, position_at_end(&content))
+            .await;
+
+        // Ignored and disabled files returned above, so this only touches
+        // files that just got a shebang header
+        if applied && config.make_executable_for(&file_path, workspace_root) {
+            let (kind, message) = match shebang::make_executable(&file_path) {
+                Ok(true) => (
+                    MessageType::INFO,
+                    format!("[Auto Header] Made {} executable", uri.path()),
+                ),
+                Ok(false) => return,
+                Err(e) => (
+                    MessageType::WARNING,
+                    format!(
+                        "[Auto Header] Failed to make {} executable: {}",
+                        uri.path(),
+                        e
+                    ),
+                ),
+            };
+            self.client.log_message(kind, message).await;
+        }
+    }
+}
+
+/// `workspace/executeCommand` that returns `preview::Preview` for the file
+/// URI (or path) given as its only argument.
+const PREVIEW_COMMAND: &str = "auto-header.preview";
+
+/// `workspace/executeCommand` that returns `explain::Explanation` for the
+/// workspace containing the optional file URI (or path) argument, or else
+/// the first workspace folder.
+const EXPLAIN_COMMAND: &str = "auto-header.explain";
+
+/// A command argument that may be a `file://` URI or a plain path.
+fn path_argument(target: &str) -> Result<PathBuf> {
+    match Url::parse(target).ok().filter(|uri| uri.scheme() == "file") {
+        Some(uri) => uri.to_file_path().map_err(|_| {
+            tower_lsp::jsonrpc::Error::invalid_params(format!("not a file URI: {}", target))
+        }),
+        None => Ok(PathBuf::from(target)),
+    }
+}
+
+/// Run filesystem scans and `git` calls off the async runtime, so a slow
+/// disk or repository can't stall the LSP message loop. `None` if the work
+/// panicked.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+    tokio::task::spawn_blocking(work).await.ok()
+}
+
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&args);
+    }
+
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+
+    let (service, socket) = LspService::new(AutoHeaderServer::new);
+    Server::new(stdin, stdout, socket).serve(service).await;
+    std::process::ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── Helpers ───────────────────────────────────────────────────────────────
+
+    /// Build an `ExtensionHeaderConfig` map from a slice of (ext, template) pairs.
+    fn ext_map(entries: &[(&str, &str)]) -> HashMap<String, ExtensionHeaderConfig> {
+        entries
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.to_string(),
+                    ExtensionHeaderConfig {
+                        template: Some(v.to_string()),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Convenience builder: only populate the fields that are `Some`.
+    fn make_partial(
+        author_name: Option<&str>,
+        author_email: Option<&str>,
+        project_name: Option<&str>,
+        copyright_holder: Option<&str>,
+        header_template: Option<&str>,
+        by_extension: Option<HashMap<String, ExtensionHeaderConfig>>,
+    ) -> PartialConfig {
+        let has_author = author_name.is_some() || author_email.is_some();
+        let has_project = project_name.is_some() || copyright_holder.is_some();
+        let has_header = header_template.is_some() || by_extension.is_some();
+        PartialConfig {
+            author: has_author.then(|| PartialAuthorConfig {
+                name: author_name.map(str::to_string),
+                email: author_email.map(str::to_string),
+            }),
+            project: has_project.then(|| PartialProjectConfig {
+                name: project_name.map(str::to_string),
+                copyright_holder: copyright_holder.map(str::to_string),
+                ..Default::default()
+            }),
+            header: has_header.then(|| PartialHeaderConfig {
+                template: header_template.map(str::to_string),
+                by_extension,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    // ── header profiles ───────────────────────────────────────────────────────
+
+    #[test]
+    fn documented_template_variables_match_the_computed_ones() {
+        let vars = Config::default().template_vars("/ws/a.rs", None, &[]);
+        let mut computed: Vec<&str> = vars.keys().copied().collect();
+        let mut documented: Vec<&str> = TEMPLATE_VARIABLES.iter().map(|(n, _)| *n).collect();
+        computed.sort_unstable();
+        documented.sort_unstable();
+        assert_eq!(computed, documented);
+    }
+
+    #[test]
+    fn php_header_profile_adds_opening_tag_before_comment_block() {
+        let header = HeaderProfile::for_extension("php")
+            .wrap("File: example.php\nAuthor: Alice", &Layout::default());
+
+        assert!(header.starts_with("<?php\n\n/*\n"));
+        assert!(header.contains(" * File: example.php\n"));
+        assert!(header.contains(" * Author: Alice\n"));
+        assert!(header.ends_with(" */\n\n"));
+        assert!(!header.contains("?>"));
+    }
+
+    #[test]
+    fn php_variants_use_the_php_profile() {
+        for ext in ["php", "phtml", "php3", "php4", "php5", "phps", "phpt"] {
+            let header =
+                HeaderProfile::for_extension(ext).wrap("File: index.php", &Layout::default());
+            assert!(
+                header.starts_with("<?php\n\n/*\n"),
+                "expected PHP prologue for .{ext}, got {header:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn shell_header_gets_interpreter_shebang_and_comment_guards() {
+        let header = HeaderProfile::for_extension("sh")
+            .wrap("File: deploy.sh\nDate: 2026-04-10", &Layout::default());
+        assert_eq!(header, "#\n# File: deploy.sh\n# Date: 2026-04-10\n#\n\n");
+
+        let config = Config::default();
+        assert_eq!(
+            config.shebang_for(Path::new("/w/a.sh"), None).as_deref(),
+            Some("#!/usr/bin/env sh")
+        );
+        assert_eq!(
+            config.shebang_for(Path::new("/w/a.bash"), None).as_deref(),
+            Some("#!/usr/bin/env bash")
+        );
+        assert_eq!(
+            config.shebang_for(Path::new("/w/a.rb"), None).as_deref(),
+            Some("#!/usr/bin/env ruby")
+        );
+        assert_eq!(config.shebang_for(Path::new("/w/a.py"), None), None);
+        assert_eq!(config.shebang_for(Path::new("/w/a.rs"), None), None);
+    }
+
+    #[test]
+    fn shebang_setting_drives_prologue_and_interpreter() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header]
+template = "{filename} runs with {interpreter}"
+
+[header.by_extension.sh]
+shebang = "bash"
+
+[header.by_extension.ts]
+shebang = "deno run --allow-read"
+
+[header.by_extension.rb]
+shebang = "none"
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        let render = |path: &str| config.render_header(path, None, &HashMap::new()).0;
+        assert_eq!(
+            render("/w/run.sh"),
+            "#!/usr/bin/env bash\n#\n# run.sh runs with bash\n#\n\n"
+        );
+        assert_eq!(
+            render("/w/tool.ts"),
+            "#!/usr/bin/env -S deno run --allow-read\n/*\n * tool.ts runs with deno\n */\n\n"
+        );
+        assert_eq!(render("/w/gen.rb"), "#\n# gen.rb runs with ruby\n#\n\n");
+
+        // A file that already starts with a shebang keeps it
+        let existing = config.with_shebang("sh", "#!/bin/dash -e");
+        assert_eq!(
+            existing.render_header("/w/run.sh", None, &HashMap::new()).0,
+            "#!/bin/dash -e\n#\n# run.sh runs with dash\n#\n\n"
+        );
+    }
+
+    #[test]
+    fn make_executable_needs_the_setting_and_a_shebang() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header.by_extension.sh]
+make_executable = true
+
+[header.by_extension.rb]
+make_executable = true
+shebang = "none"
+
+[header.by_extension.ts]
+make_executable = true
+shebang = "deno run"
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        assert!(config.make_executable_for(Path::new("/w/a.sh"), None));
+        assert!(config.make_executable_for(Path::new("/w/a.ts"), None));
+        assert!(!config.make_executable_for(Path::new("/w/a.rb"), None));
+        assert!(!config.make_executable_for(Path::new("/w/a.bash"), None));
+    }
+
+    #[test]
+    fn owners_come_from_codeowners_and_skip_files_outside_the_root() {
+        let ws = crate::test_support::Workspace::new("owners");
+        ws.write("root/CODEOWNERS", "*.rs @core\n");
+        ws.write(
+            "root/.auto-header.toml",
+            "[header]\ntemplate = \"{owners}\"\n",
+        );
+        let root = ws.root().join("root");
+        let config = Config::from_sources(&crate::test_support::project_sources(&root));
+        let render = |file: &Path| {
+            config
+                .render_header(file.to_str().unwrap(), Some(&root), &HashMap::new())
+                .0
+        };
+        assert_eq!(render(&root.join("src/a.rs")), "/*\n * @core\n */\n\n");
+        assert_eq!(render(&ws.root().join("other/a.rs")), "/*\n */\n\n");
+    }
+
+    #[test]
+    fn python_profile_uses_encoding_line_and_docstring_body() {
+        let header = HeaderProfile::for_extension("py").wrap("File: script.py", &Layout::default());
+
+        assert_eq!(
+            header,
+            "# -*- coding: utf-8 -*-\n\"\"\"\nFile: script.py\n\"\"\"\n\n"
+        );
+    }
+
+    #[test]
+    fn python_cookie_style_and_script_shebang_are_configurable() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header]
+template = "{filename} ({interpreter})"
+
+[header.by_extension.py]
+encoding_cookie = false
+
+[header.by_extension.pyw]
+profile = "python-comment"
+
+[header.by_extension.pyx]
+profile = "python-docstring"
+encoding_cookie = true
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        let root = Path::new("/w");
+        let render = |path: &str| config.render_header(path, Some(root), &HashMap::new()).0;
+        assert_eq!(render("/w/lib.py"), "\"\"\"\nlib.py (python3)\n\"\"\"\n\n");
+        assert_eq!(
+            render("/w/bin/tool.py"),
+            "#!/usr/bin/env python3\n\"\"\"\ntool.py (python3)\n\"\"\"\n\n"
+        );
+        assert_eq!(
+            render("/w/scripts/gui.pyw"),
+            "#!/usr/bin/env python3\n# gui.pyw (python3)\n\n"
+        );
+        assert_eq!(
+            render("/w/scripts/ext.pyx"),
+            "#!/usr/bin/env python3\n# -*- coding: utf-8 -*-\n\"\"\"\next.pyx (python3)\n\"\"\"\n\n"
+        );
+        assert_eq!(
+            config.profile_for("pyx").describe(),
+            "block comment \"\"\" \"\"\" after `# -*- coding: utf-8 -*-`"
+        );
+        // The default keeps the cookie, and `bin` above the workspace root doesn't count
+        let default = Config::default();
+        let shebang = default.shebang_for(
+            Path::new("/usr/bin/app/main.py"),
+            Some(Path::new("/usr/bin/app")),
+        );
+        assert_eq!(shebang, None);
+        assert!(HeaderProfile::for_extension("py").coding_cookie);
+    }
+
+    #[test]
+    fn known_extensions_all_have_a_dedicated_profile() {
+        for ext in HeaderProfile::KNOWN_EXTENSIONS {
+            assert!(HeaderProfile::builtin(ext).is_some(), ".{ext}");
+        }
+        assert!(HeaderProfile::builtin("unknown").is_none());
+    }
+
+    #[test]
+    fn comment_terminators_in_content_never_close_the_comment() {
+        let hostile = "a */ b /* c --> d --!> e \"\"\" f ''' g --]] h ]] i *) j (* k";
+        let extensions = HeaderProfile::KNOWN_EXTENSIONS.iter().chain(&["unknown"]);
+        for ext in extensions {
+            let style = HeaderProfile::for_extension(ext).comment_style;
+            let wrapped = style.wrap(hostile, &Layout::default());
+            let lines: Vec<&str> = wrapped.lines().collect();
+            match style {
+                CommentStyle::Block { nested, .. } => {
+                    let (start, end) = (lines[0], lines[lines.len() - 1].trim());
+                    // Lua closes on any `]]`; nesting languages also count openers
+                    let closer = if end == "--]]" { "]]" } else { end };
+                    let inner = lines[1..lines.len() - 1].join("\n");
+                    assert!(!inner.contains(closer), ".{ext}: {wrapped:?}");
+                    if nested {
+                        assert!(!inner.contains(start), ".{ext}: {wrapped:?}");
+                    }
+                }
+                CommentStyle::Line { prefix } => {
+                    assert!(lines.iter().all(|l| l.starts_with(prefix)), ".{ext}");
+                }
+                CommentStyle::HtmlComment => {
+                    let inner = lines[1..lines.len() - 1].join("\n");
+                    assert!(!inner.contains("-->") && !inner.contains("--!>"), ".{ext}");
+                }
+            }
+            assert!(wrapped.contains('a') && wrapped.contains('k'), ".{ext}");
+        }
+    }
+
+    #[test]
+    fn terminators_are_escaped_per_style() {
+        let wrap = |ext: &str, content: &str| {
+            HeaderProfile::for_extension(ext).wrap(content, &Layout::default())
+        };
+        assert_eq!(wrap("rs", "a */ b"), "/*\n * a *\\/ b\n */\n\n");
+        assert_eq!(wrap("rs", "src/*.rs"), "/*\n * src/\\*.rs\n */\n\n");
+        assert_eq!(wrap("c", "src/*.c */"), "/*\n * src/*.c *\\/\n */\n\n");
+        assert_eq!(wrap("html", "a --> b"), "<!--\n  a --&gt; b\n-->\n\n");
+        assert_eq!(wrap("lua", "t[a[1]]"), "--[[\nt[a[1] ]\n--]]\n\n");
+        assert_eq!(wrap("ml", "(*) f"), "(*\n * ( * ) f\n *)\n\n");
+        assert_eq!(wrap("sh", "a */ --> b"), "#\n# a */ --> b\n#\n\n");
+    }
+
+    #[test]
+    fn editorconfig_sets_html_indent_and_wraps_long_lines() {
+        let ws = crate::test_support::Workspace::new("layout");
+        ws.write(
+            ".editorconfig",
+            "root = true\n[*.html]\nindent_style = tab\n[*.rs]\nindent_size = 4\nmax_line_length = 20\n",
+        );
+        let html = Layout::for_file(&ws.root().join("index.html"));
+        assert_eq!(
+            HeaderProfile::for_extension("html").wrap("File: index.html", &html),
+            "<!--\n\tFile: index.html\n-->\n\n"
+        );
+
+        let rs = Layout::for_file(&ws.root().join("src/lib.rs"));
+        assert_eq!((rs.indent.as_str(), rs.max_line_length), ("    ", Some(20)));
+        let header =
+            HeaderProfile::for_extension("rs").wrap("Licensed under the Apache License", &rs);
+        assert_eq!(
+            header,
+            "/*\n * Licensed under\n * the Apache\n * License\n */\n\n"
+        );
+        assert!(header.lines().all(|line| line.chars().count() <= 20));
+    }
+
+    #[test]
+    fn wrap_width_reflows_paragraphs_and_overrides_per_extension() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header]
+wrap_width = 30
+template = """
+File: {filename}
+
+This file is part of a project that is licensed under the MIT license.
+"""
+
+[header.by_extension.sql]
+wrap_width = 0
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        let (header, _) = config.render_header("/nonexistent/a.rs", None, &HashMap::new());
+        assert_eq!(
+            header,
+            "/*\n * File: a.rs\n *\n * This file is part of a\n * project that is licensed\n * under the MIT license.\n */\n\n"
+        );
+        assert!(header.lines().all(|line| line.chars().count() <= 30));
+
+        let (header, _) = config.render_header("/nonexistent/a.sql", None, &HashMap::new());
+        assert!(header.contains("-- This file is part of a project that is licensed"));
+    }
+
+    #[test]
+    fn box_styles_draw_rules_and_aligned_edges() {
+        let layout = |box_style| Layout {
+            max_line_length: Some(20),
+            box_style,
+            ..Layout::default()
+        };
+        let wrap = |ext: &str, box_style| {
+            HeaderProfile::for_extension(ext)
+                .comment_style
+                .wrap("File: a\n\nBy: Bo", &layout(box_style))
+        };
+        for (ext, style, expected) in [
+            ("rs", BoxStyle::Stars, "/*******************\n * File: a\n *\n * By: Bo\n ******************/\n"),
+            ("rs", BoxStyle::Box, "/*******************\n * File: a         *\n *                 *\n * By: Bo          *\n ******************/\n"),
+            ("sh", BoxStyle::Equals, "# ==================\n# File: a\n#\n# By: Bo\n# ==================\n"),
+            ("sh", BoxStyle::Box, "# **************** #\n# File: a          #\n#                  #\n# By: Bo           #\n# **************** #\n"),
+            ("py", BoxStyle::Dashes, "\"\"\"\n--------------------\nFile: a\n\nBy: Bo\n--------------------\n\"\"\"\n"),
+            ("html", BoxStyle::Box, "<!--\n  ******************\n  File: a\n\n  By: Bo\n  ******************\n-->\n"),
+        ] {
+            assert_eq!(wrap(ext, style), expected, ".{ext} {style:?}");
+        }
+    }
+
+    #[test]
+    fn banners_widen_for_long_lines_and_every_profile_stays_closed() {
+        let layout = Layout {
+            box_style: BoxStyle::Box,
+            ..Layout::default()
+        };
+        let long = "x".repeat(100);
+        let header = HeaderProfile::for_extension("c")
+            .comment_style
+            .wrap(&long, &layout);
+        assert!(header.lines().all(|l| l.chars().count() == 105), "{header}");
+
+        let extensions = HeaderProfile::KNOWN_EXTENSIONS.iter().chain(&["unknown"]);
+        for ext in extensions {
+            let style = HeaderProfile::for_extension(ext).comment_style;
+            let plain = style.wrap("a", &Layout::default());
+            let boxed = style.wrap("a", &layout);
+            if let CommentStyle::Line { prefix } = style {
+                assert!(
+                    boxed.lines().all(|l| l.starts_with(prefix)),
+                    ".{ext}: {boxed}"
+                );
+            } else {
+                // Same opening and closing markers, only decorated
+                let (first, last) = (plain.lines().next(), plain.lines().last());
+                assert!(boxed.starts_with(first.unwrap().trim()), ".{ext}: {boxed}");
+                assert!(
+                    boxed.trim_end().ends_with(last.unwrap().trim()),
+                    ".{ext}: {boxed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn named_profiles_replace_the_extension_default() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[author]
+name = "Ann"
+
+[project]
+name = "p"
+copyright_holder = "Acme"
 
-        // Find the workspace root by checking which workspace folder contains this file
-        let folders = self.workspace_folders.read().await;
-        let workspace_root = folders
-            .iter()
-            .find(|folder| file_path.starts_with(folder))
-            .map(|p| p.as_path());
+[header]
+template = "File: {filename}"
 
-        // Only insert header if:
-        // 1. File is completely empty
-        // 2. A .auto-header.toml config file exists in search paths
-        let config_exists = Config::config_exists(workspace_root);
+[header.by_extension.rs]
+profile = "rust-doc"
 
-        if !content.trim().is_empty() {
-            self.client
-                .log_message(
-                    MessageType::INFO,
-                    format!("[Auto Header] Skipping non-empty file: {}", uri.path()),
-                )
-                .await;
-            return;
-        }
+[header.by_extension.js]
+profile = "jsdoc"
 
-        if !config_exists {
-            self.client
-                .log_message(
-                    MessageType::INFO,
-                    format!(
-                        "[Auto Header] No .auto-header.toml found, skipping: {}",
-                        uri.path()
-                    ),
-                )
-                .await;
-            return;
-        }
+[header.by_extension.ts]
+profile = "jsdoc"
+template = "Module {filename} */ done"
 
-        // Both conditions met, insert header
-        self.client
-            .log_message(
-                MessageType::INFO,
-                format!("[Auto Header] Inserting header for: {}", uri.path()),
+[header.by_extension.py]
+profile = "python-docstring"
+
+[header.by_extension.go]
+profile = "go-doc"
+body = "package main\n"
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        let render = |path: &str| config.render_header(path, None, &HashMap::new()).0;
+        let year = chrono::Local::now().format("%Y").to_string();
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+        assert_eq!(render("/w/lib.rs"), "//! File: lib.rs\n\n");
+        assert_eq!(
+            render("/w/app.js"),
+            format!(
+                "/**\n * @file app.js\n * @author Ann\n * @date {date}\n * @copyright {year} Acme\n */\n\n"
             )
-            .await;
+        );
+        assert_eq!(
+            render("/w/app.ts"),
+            "/**\n * Module app.ts *\\/ done\n */\n\n"
+        );
+        assert_eq!(render("/w/tool.py"), "\"\"\"\nFile: tool.py\n\"\"\"\n\n");
+        assert_eq!(render("/w/main.go"), "// File: main.go\npackage main\n");
+        assert_eq!(config.profile_for("rs").describe(), "line comment //!");
+    }
+
+    #[test]
+    fn python_docstring_switches_quotes_when_content_has_triple_quotes() {
+        assert_eq!(
+            HeaderProfile::for_extension("py").wrap("Say \"\"\"hi\"\"\"", &Layout::default()),
+            "# -*- coding: utf-8 -*-\n'''\nSay \"\"\"hi\"\"\"\n'''\n\n"
+        );
+        assert_eq!(
+            HeaderProfile::for_extension("py").wrap("\"\"\" and '''", &Layout::default()),
+            "# -*- coding: utf-8 -*-\n\"\"\"\n\\\"\\\"\\\" and '''\n\"\"\"\n\n"
+        );
+    }
+
+    // ── multiple authors ──────────────────────────────────────────────────────
+
+    #[test]
+    fn toml_authors_array_parses_with_optional_email() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[[authors]]
+name = "Alice"
+email = "alice@example.com"
+
+[[authors]]
+name = "Bob"
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        assert_eq!(
+            config.author_lines(),
+            vec!["Alice <alice@example.com>", "Bob"]
+        );
+    }
 
-        let header = self.generate_header(file_path_str, workspace_root);
+    #[test]
+    fn authors_falls_back_to_single_author() {
+        let config = Config::default();
+        assert_eq!(config.author_lines(), vec!["Auto Header <auto@header.dev>"]);
+    }
 
-        // Create a text edit to insert the header at the beginning
-        let edit = TextEdit {
-            range: Range {
-                start: Position {
-                    line: 0,
-                    character: 0,
+    #[test]
+    fn higher_priority_authors_array_replaces_lower() {
+        let low = PartialConfig {
+            authors: Some(vec![
+                AuthorConfig {
+                    name: "A".into(),
+                    email: String::new(),
                 },
-                end: Position {
-                    line: 0,
-                    character: 0,
+                AuthorConfig {
+                    name: "B".into(),
+                    email: String::new(),
                 },
-            },
-            new_text: header,
+            ]),
+            ..Default::default()
         };
-
-        // Apply the workspace edit
-        let mut changes = std::collections::HashMap::new();
-        changes.insert(uri.clone(), vec![edit]);
-
-        let workspace_edit = WorkspaceEdit {
-            changes: Some(changes),
+        let high = PartialConfig {
+            authors: Some(vec![AuthorConfig {
+                name: "C".into(),
+                email: String::new(),
+            }]),
             ..Default::default()
         };
+        assert_eq!(high.merge(low).into_config().author_lines(), vec!["C"]);
+    }
 
-        // Send the edit to the client
-        if let Err(e) = self.client.apply_edit(workspace_edit).await {
-            self.client
-                .log_message(
-                    MessageType::ERROR,
-                    format!("Failed to apply header edit: {:?}", e),
-                )
-                .await;
-        } else {
-            self.client
-                .log_message(
-                    MessageType::INFO,
-                    format!("Header inserted for {}", uri.path()),
-                )
-                .await;
-        }
+    #[test]
+    fn authors_render_one_per_line_inside_comment() {
+        let mut config = Config::default();
+        config.header.template = "Authors: {authors}".to_string();
+        config.authors = vec![
+            AuthorConfig {
+                name: "Alice".into(),
+                email: "a@x.dev".into(),
+            },
+            AuthorConfig {
+                name: "Bob".into(),
+                email: String::new(),
+            },
+        ];
+        assert_eq!(
+            config.render_header("/tmp/lib.rs", None, &HashMap::new()).0,
+            "/*\n * Authors: Alice <a@x.dev>\n *          Bob\n */\n\n"
+        );
+        assert_eq!(
+            config.render_header("/tmp/run.sh", None, &HashMap::new()).0,
+            "#!/usr/bin/env sh\n#\n# Authors: Alice <a@x.dev>\n#          Bob\n#\n\n"
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // ── environment placeholders ──────────────────────────────────────────────
 
-    // ── Helpers ───────────────────────────────────────────────────────────────
+    #[test]
+    fn env_placeholders_resolve_in_config_fields_and_templates() {
+        // Only this test uses these names, and the second one is never set
+        std::env::set_var("AUTO_HEADER_TEST_NAME", "Build Bot");
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[author]
+name = "{env:AUTO_HEADER_TEST_NAME}"
+email = "{env:AUTO_HEADER_TEST_MAIL:-bot@example.com}"
 
-    /// Build an `ExtensionHeaderConfig` map from a slice of (ext, template) pairs.
-    fn ext_map(entries: &[(&str, &str)]) -> HashMap<String, ExtensionHeaderConfig> {
-        entries
-            .iter()
-            .map(|(k, v)| {
-                (
-                    k.to_string(),
-                    ExtensionHeaderConfig {
-                        template: v.to_string(),
-                    },
-                )
-            })
-            .collect()
+[header]
+template = "{author} <{email}> {env:AUTO_HEADER_TEST_NAME}"
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config().resolve_env();
+        assert_eq!(config.author.name, "Build Bot");
+        assert_eq!(config.author.email, "bot@example.com");
+        assert_eq!(
+            config.render_header("/tmp/a.sql", None, &HashMap::new()).0,
+            "-- Build Bot <bot@example.com> Build Bot\n\n"
+        );
     }
 
-    /// Convenience builder: only populate the fields that are `Some`.
-    fn make_partial(
-        author_name: Option<&str>,
-        author_email: Option<&str>,
-        project_name: Option<&str>,
-        copyright_holder: Option<&str>,
-        header_template: Option<&str>,
-        by_extension: Option<HashMap<String, ExtensionHeaderConfig>>,
-    ) -> PartialConfig {
-        let has_author = author_name.is_some() || author_email.is_some();
-        let has_project = project_name.is_some() || copyright_holder.is_some();
-        let has_header = header_template.is_some() || by_extension.is_some();
-        PartialConfig {
-            author: has_author.then(|| PartialAuthorConfig {
-                name: author_name.map(str::to_string),
-                email: author_email.map(str::to_string),
-            }),
-            project: has_project.then(|| PartialProjectConfig {
-                name: project_name.map(str::to_string),
-                copyright_holder: copyright_holder.map(str::to_string),
-            }),
-            header: has_header.then(|| PartialHeaderConfig {
-                template: header_template.map(str::to_string),
-                by_extension,
-            }),
-        }
+    #[test]
+    fn filters_apply_to_header_variables() {
+        let mut config = Config::default();
+        config.header.template =
+            "#ifndef {filename|stem|upper|ident}_H\n{project|snake} {author|default(\"x\")}"
+                .to_string();
+        config.project.name = "My Project".to_string();
+        assert_eq!(
+            config
+                .render_header("/src/ring-buffer.h", None, &HashMap::new())
+                .0,
+            "/*\n * #ifndef RING_BUFFER_H\n * my_project Auto Header\n */\n\n"
+        );
     }
 
-    // ── header profiles ───────────────────────────────────────────────────────
+    // ── body templates ────────────────────────────────────────────────────────
 
     #[test]
-    fn php_header_profile_adds_opening_tag_before_comment_block() {
-        let header = HeaderProfile::for_extension("php").wrap("File: example.php\nAuthor: Alice");
+    fn body_is_emitted_raw_after_the_wrapped_header() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header]
+template = "File: {filename}"
+
+[header.by_extension.h]
+body = """
+#ifndef {filename|stem|upper|ident}_H
+#define {filename|stem|upper|ident}_H
+
+{cursor}
+
+#endif
+"""
+"#,
+        )
+        .unwrap();
+        let header = Header::new(
+            partial
+                .into_config()
+                .render_header("/src/ring-buffer.h", None, &HashMap::new())
+                .0,
+        );
+        assert_eq!(
+            header.text,
+            "/*\n * File: ring-buffer.h\n */\n\n\
+             #ifndef RING_BUFFER_H\n#define RING_BUFFER_H\n\n\n\n#endif\n"
+        );
+        assert_eq!(header.selection.map(|r| r.start), Some(Position::new(7, 0)));
+    }
 
-        assert!(header.starts_with("<?php\n\n/*\n"));
-        assert!(header.contains(" * File: example.php\n"));
-        assert!(header.contains(" * Author: Alice\n"));
-        assert!(header.ends_with(" */\n\n"));
-        assert!(!header.contains("?>"));
+    #[test]
+    fn extension_without_template_keeps_default_header() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header]
+template = "File: {filename}"
+
+[header.by_extension.py]
+body = "if __name__ == \"__main__\":\n    {cursor}\n"
+"#,
+        )
+        .unwrap();
+        let header = Header::new(
+            partial
+                .into_config()
+                .render_header("/tmp/tool.py", None, &HashMap::new())
+                .0,
+        );
+        assert_eq!(
+            header.text,
+            "# -*- coding: utf-8 -*-\n\"\"\"\nFile: tool.py\n\"\"\"\n\n\
+             if __name__ == \"__main__\":\n    \n"
+        );
+        assert_eq!(header.selection.map(|r| r.start), Some(Position::new(6, 4)));
     }
 
     #[test]
-    fn php_variants_use_the_php_profile() {
-        for ext in ["php", "phtml", "php3", "php4", "php5", "phps", "phpt"] {
-            let header = HeaderProfile::for_extension(ext).wrap("File: index.php");
-            assert!(
-                header.starts_with("<?php\n\n/*\n"),
-                "expected PHP prologue for .{ext}, got {header:?}"
-            );
-        }
+    fn escaped_cursor_placeholder_stays_literal() {
+        let mut config = Config::default();
+        config.header.template = "Use {{cursor}} for the caret{cursor}".to_string();
+        let header = Header::new(config.render_header("/tmp/a.sql", None, &HashMap::new()).0);
+        assert_eq!(header.text, "-- Use {cursor} for the caret\n\n");
+        assert_eq!(
+            header.selection.map(|r| r.start),
+            Some(Position::new(0, 29))
+        );
     }
 
     #[test]
-    fn shell_profile_still_emits_shebang_and_comment_guards() {
-        let header = HeaderProfile::for_extension("sh").wrap("File: deploy.sh\nDate: 2026-04-10");
+    fn cursor_column_counts_utf16_units() {
+        let header = Header::new(format!("// 日本😀{}x", snippet::CURSOR_MARKER));
+        assert_eq!(header.text, "// 日本😀x");
+        assert_eq!(header.selection.map(|r| r.start), Some(Position::new(0, 7)));
+    }
 
+    #[test]
+    fn package_variable_feeds_body_template() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[project]
+source_roots = ["java"]
+
+[header.by_extension.java]
+template = "{filename}"
+body = "package {package};\n"
+"#,
+        )
+        .unwrap();
+        let header = partial.into_config().render_header(
+            "/ws/java/com/acme/App.java",
+            Some(Path::new("/ws")),
+            &HashMap::new(),
+        );
         assert_eq!(
             header,
-            "#!/usr/bin/env bash\n#\n# File: deploy.sh\n# Date: 2026-04-10\n#\n\n"
+            (
+                "/*\n * App.java\n */\n\npackage com.acme;\n".to_string(),
+                Vec::new()
+            )
         );
     }
 
     #[test]
-    fn python_profile_uses_encoding_line_and_docstring_body() {
-        let header = HeaderProfile::for_extension("py").wrap("File: script.py");
+    fn extension_encoding_overrides_header_encoding() {
+        let partial: PartialConfig = toml::from_str(
+            "[header]\nline_ending = \"crlf\"\nbom = true\n[header.by_extension.sh]\nline_ending = \"lf\"\nbom = false\n",
+        )
+        .unwrap();
+        let config = partial.into_config();
+        let ps1 = config.encoding_for(Path::new("/nonexistent/run.ps1"));
+        assert_eq!(ps1.apply("# a\n"), "\u{feff}# a\r\n");
+        let sh = config.encoding_for(Path::new("/nonexistent/run.sh"));
+        assert_eq!(sh.apply("# a\n"), "# a\n");
+    }
+
+    #[test]
+    fn unresolved_placeholders_follow_policy() {
+        let config = |policy: &str| {
+            let partial: PartialConfig = toml::from_str(&format!(
+                "[header]\nunresolved = \"{}\"\n[header.by_extension.sql]\ntemplate = \"{{filename}} {{nope}}\"\nbody = \"{{nope|upper}} {{{{x}}}} ${{1:y}}\\n\"\n",
+                policy
+            ))
+            .unwrap();
+            partial.into_config()
+        };
+        let (kept, unresolved) = config("keep").render_header("/t/a.sql", None, &HashMap::new());
+        assert_eq!(kept, "-- a.sql {nope}\n\n{nope|upper} {x} ${1:y}\n");
+        assert_eq!(unresolved, vec!["{nope}", "{nope|upper}"]);
+
+        let (removed, _) = config("remove").render_header("/t/a.sql", None, &HashMap::new());
+        assert_eq!(removed, "-- a.sql\n\n{x} ${1:y}\n");
+    }
+
+    #[test]
+    fn tab_stops_become_snippet_with_plain_fallback() {
+        let mut config = Config::default();
+        config.header.template = "{filename} - ${1:Brief description}\nTicket: ${2:}".to_string();
+        let header = Header::new(
+            config
+                .render_header("/tmp/app.sql", None, &HashMap::new())
+                .0,
+        );
 
         assert_eq!(
-            header,
-            "# -*- coding: utf-8 -*-\n\"\"\"\nFile: script.py\n\"\"\"\n\n"
+            header.text,
+            "-- app.sql - Brief description\n-- Ticket: \n\n"
+        );
+        assert_eq!(
+            header.snippet.as_deref(),
+            Some("-- app.sql - ${1:Brief description}\n-- Ticket: ${2}\n\n")
+        );
+        // Without snippet support the description default is pre-selected
+        assert_eq!(
+            header.selection,
+            Some(Range::new(Position::new(0, 13), Position::new(0, 30)))
+        );
+    }
+
+    #[test]
+    fn header_without_tab_stops_has_no_snippet() {
+        let header = Header::new(format!("# $Id$ {}\n", snippet::CURSOR_MARKER));
+        assert_eq!(header.text, "# $Id$ \n");
+        assert!(header.snippet.is_none());
+    }
+
+    #[test]
+    fn substituted_values_never_become_tab_stops() {
+        let mut config = Config::default();
+        config.header.template = "{filepath} {prompt:Note} ${1:desc}".to_string();
+        config.header.by_extension.insert(
+            "sql".to_string(),
+            ExtensionHeaderConfig {
+                body: Some("-- $${VAR}\n".to_string()),
+                ..Default::default()
+            },
+        );
+        let answers = HashMap::from([("Note".to_string(), "$${2:y}".to_string())]);
+        let header = Header::new(config.render_header("/tmp/${1:x}/a.sql", None, &answers).0);
+        assert_eq!(
+            header.text,
+            "-- /tmp/${1:x}/a.sql $${2:y} desc\n\n-- $${VAR}\n"
+        );
+        assert_eq!(
+            header.snippet.as_deref(),
+            Some("-- /tmp/\\${1:x\\}/a.sql \\$\\${2:y\\} ${1:desc}\n\n-- \\$\\${VAR\\}\n")
+        );
+    }
+
+    // ── prompts ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn prompts_use_answers_then_configured_defaults() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header]
+template = "{prompt:Description}\nTicket: {prompt:Ticket|default(\"none\")}\nArea: {prompt:Area|upper}"
+
+[prompts.Area]
+options = ["core", "ui"]
+default = "core"
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        assert_eq!(
+            config.prompt_labels("sql"),
+            vec!["Description", "Ticket", "Area"]
+        );
+
+        let answers = HashMap::from([("Description".to_string(), "Billing views".to_string())]);
+        assert_eq!(
+            config.render_header("/tmp/a.sql", None, &answers).0,
+            "-- Billing views\n-- Ticket: none\n-- Area: CORE\n\n"
+        );
+    }
+
+    #[test]
+    fn prompts_without_options_become_tab_stops_after_the_templates() {
+        let partial: PartialConfig = toml::from_str(
+            r#"
+[header]
+template = "${1:Summary}\nTicket: {prompt:Ticket}\nArea: {prompt:Area}"
+
+[header.by_extension.sql]
+body = "-- {prompt:Owner} ${2:}\n"
+
+[prompts.Area]
+options = ["core", "ui"]
+
+[prompts.Owner]
+default = "data}team"
+"#,
+        )
+        .unwrap();
+        let config = partial.into_config();
+        let answers = config.free_text_prompts("sql");
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers["Ticket"], "${3:Ticket}");
+        assert_eq!(answers["Owner"], "${4:data\\}team}");
+
+        let header = Header::new(config.render_header("/tmp/a.sql", None, &answers).0);
+        assert_eq!(
+            header.snippet.as_deref(),
+            Some(
+                "-- ${1:Summary}\n-- Ticket: ${3:Ticket}\n-- Area: \n\n-- ${4:data\\}team} ${2}\n"
+            )
+        );
+        assert_eq!(
+            header.text,
+            "-- Summary\n-- Ticket: Ticket\n-- Area: \n\n-- data}team \n"
         );
     }
 
+    #[test]
+    fn prompt_configs_merge_per_label() {
+        let home: PartialConfig =
+            toml::from_str("[prompts.A]\ndefault = \"home\"\n[prompts.B]\ndefault = \"home\"\n")
+                .unwrap();
+        let project: PartialConfig =
+            toml::from_str("[prompts.A]\noptions = [\"x\"]\ndefault = \"project\"\n").unwrap();
+        let prompts = project.merge(home).into_config().prompts;
+        assert_eq!(prompts["A"].default, "project");
+        assert_eq!(prompts["B"].default, "home");
+    }
+
+    // ── insertion rules ───────────────────────────────────────────────────────
+
+    #[test]
+    fn insert_rules_default_to_new_files_and_merge_per_key() {
+        let config = PartialConfig::default().into_config();
+        assert!(config.insert.only_created);
+        assert_eq!(config.insert.max_age_secs, 0);
+        assert!(config.insert.skip_git_tracked);
+
+        let home: PartialConfig =
+            toml::from_str("[insert]\nonly_created = false\nmax_age_secs = 60\n").unwrap();
+        let project: PartialConfig =
+            toml::from_str("[insert]\nmax_age_secs = 5\nskip_git_tracked = false\n").unwrap();
+        let insert = project.merge(home).into_config().insert;
+        assert!(!insert.only_created);
+        assert_eq!(insert.max_age_secs, 5);
+        assert!(!insert.skip_git_tracked);
+    }
+
+    #[test]
+    fn ignore_patterns_accumulate_across_sources() {
+        let home: PartialConfig =
+            toml::from_str("[ignore]\npatterns = [\"vendor/\"]\ngitignore = false\n").unwrap();
+        let project: PartialConfig =
+            toml::from_str("[ignore]\npatterns = [\"!vendor/ours/\"]\n").unwrap();
+        let ignore = project.merge(home).into_config().ignore;
+        assert_eq!(ignore.patterns, vec!["vendor/", "!vendor/ours/"]);
+        assert!(!ignore.gitignore);
+        assert!(!ignore.outside_workspace);
+
+        assert!(PartialConfig::default().into_config().ignore.gitignore);
+    }
+
+    // ── enable/disable switches ───────────────────────────────────────────────
+
+    #[test]
+    fn project_can_disable_globally_enabled_headers() {
+        let home: PartialConfig = toml::from_str("[author]\nname = \"Me\"\n").unwrap();
+        let project: PartialConfig = toml::from_str("enabled = false\n").unwrap();
+        let config = project.merge(home).into_config();
+        assert!(!config.enabled);
+        assert!(!config.is_enabled_for("rs"));
+        assert!(PartialConfig::default().into_config().is_enabled_for("rs"));
+    }
+
+    #[test]
+    fn extension_switch_overrides_top_level() {
+        let config: PartialConfig = toml::from_str(
+            r#"
+enabled = false
+
+[header.by_extension.py]
+enabled = true
+
+[header.by_extension.json]
+enabled = false
+"#,
+        )
+        .unwrap();
+        let config = config.into_config();
+        assert!(config.is_enabled_for("py"));
+        assert!(!config.is_enabled_for("rs"));
+        assert!(!config.is_enabled_for("json"));
+    }
+
+    #[test]
+    fn by_extension_entries_merge_field_by_field() {
+        let home: PartialConfig = toml::from_str(
+            "[header.by_extension.h]\ntemplate = \"home h\"\nbody = \"#pragma once\\n\"\n",
+        )
+        .unwrap();
+        let project: PartialConfig =
+            toml::from_str("[header.by_extension.h]\nenabled = false\n").unwrap();
+        let config = project.merge(home.clone()).into_config();
+        let h = &config.header.by_extension["h"];
+        assert_eq!(h.template.as_deref(), Some("home h"));
+        assert_eq!(h.body.as_deref(), Some("#pragma once\n"));
+        assert_eq!(h.enabled, Some(false));
+
+        // An empty string clears what the lower config set
+        let project: PartialConfig =
+            toml::from_str("[header.by_extension.h]\ntemplate = \"\"\nbody = \"\"\n").unwrap();
+        let config = project.merge(home).into_config();
+        assert_eq!(config.template_for_extension("h"), config.header.template);
+        assert_eq!(config.body_for_extension("h"), None);
+    }
+
     // ── into_config ───────────────────────────────────────────────────────────
 
     /// An all-empty PartialConfig must produce the exact built-in defaults.
@@ -973,9 +3688,9 @@ mod tests {
         let config = project.merge(home).merge(platform).into_config();
         let by_ext = &config.header.by_extension;
 
-        assert_eq!(by_ext["rs"].template, "project rs"); // project wins
-        assert_eq!(by_ext["go"].template, "home go"); // home unique key preserved
-        assert_eq!(by_ext["py"].template, "platform py"); // platform unique key preserved
+        assert_eq!(by_ext["rs"].template.as_deref(), Some("project rs")); // project wins
+        assert_eq!(by_ext["go"].template.as_deref(), Some("home go")); // home unique key preserved
+        assert_eq!(by_ext["py"].template.as_deref(), Some("platform py")); // platform unique key preserved
     }
 
     /// When only home and platform set the same extension key, home wins.
@@ -1003,7 +3718,10 @@ mod tests {
             .merge(platform)
             .into_config();
 
-        assert_eq!(config.header.by_extension["ts"].template, "home ts");
+        assert_eq!(
+            config.header.by_extension["ts"].template.as_deref(),
+            Some("home ts")
+        );
     }
 
     // ── TOML deserialization ──────────────────────────────────────────────────
@@ -1110,19 +3828,10 @@ rs = { template = "Rust (project)" }
         let config = project.merge(home).merge(platform).into_config();
         let by_ext = &config.header.by_extension;
 
-        assert_eq!(by_ext["rs"].template, "Rust (project)"); // project wins
-        assert_eq!(by_ext["go"].template, "Go (home)"); // home unique key
-        assert_eq!(by_ext["py"].template, "Python (platform)"); // platform unique key
-                                                                // header.template only set in platform, should survive the merge
+        assert_eq!(by_ext["rs"].template.as_deref(), Some("Rust (project)")); // project wins
+        assert_eq!(by_ext["go"].template.as_deref(), Some("Go (home)")); // home unique key
+        assert_eq!(by_ext["py"].template.as_deref(), Some("Python (platform)")); // platform unique key
+                                                                                 // header.template only set in platform, should survive the merge
         assert_eq!(config.header.template, "default");
     }
 }
-
-#[tokio::main]
-async fn main() {
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-
-    let (service, socket) = LspService::new(AutoHeaderServer::new);
-    Server::new(stdin, stdout, socket).serve(service).await;
-}
@@ -0,0 +1,340 @@
+//! Derive `{package}`, `{namespace}` and `{module}` from a file's location.
+//!
+//! The directory of the file is taken relative to the first configured source
+//! root found in its path (e.g. `src/main/java`), falling back to the
+//! workspace root. PHP namespaces honour the PSR-4 map in `composer.json` and
+//! Go module paths come from the nearest `go.mod`.
+
+use std::path::{Path, PathBuf};
+
+/// Source roots used when `[project] source_roots` isn't configured. More
+/// specific roots come first since the first match wins.
+pub fn default_source_roots() -> Vec<String> {
+    [
+        "src/main/java",
+        "src/main/kotlin",
+        "src/main/scala",
+        "src/test/java",
+        "src/test/kotlin",
+        "src/test/scala",
+        "src",
+        "lib",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// `{package}`: the Go package name (directory name), or the dotted
+/// directory path below the source root for Java, Kotlin, Scala and others.
+pub fn package(file: &Path, workspace_root: Option<&Path>, source_roots: &[String]) -> String {
+    let dirs = source_dirs(file, workspace_root, source_roots);
+    if extension(file) == "go" {
+        return go_package(file, &dirs);
+    }
+    dirs.join(".")
+}
+
+/// `{namespace}`: PSR-4 namespace for PHP, `a::b` for C++, dotted otherwise.
+pub fn namespace(file: &Path, workspace_root: Option<&Path>, source_roots: &[String]) -> String {
+    let ext = extension(file);
+    if is_php(ext) {
+        if let Some(ns) = workspace_root.and_then(|root| psr4_namespace(file, root)) {
+            return ns;
+        }
+        return source_dirs(file, workspace_root, source_roots).join("\\");
+    }
+    let separator = match ext {
+        "cpp" | "hpp" | "cc" | "hh" | "cxx" | "hxx" | "h" => "::",
+        _ => ".",
+    };
+    source_dirs(file, workspace_root, source_roots).join(separator)
+}
+
+/// `{module}`: Go import path from `go.mod`, dotted Python module path, or
+/// the file stem (e.g. a Verilog module named after its file).
+pub fn module(file: &Path, workspace_root: Option<&Path>, source_roots: &[String]) -> String {
+    let stem = file.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    match extension(file) {
+        "go" => go_module_path(file, workspace_root).unwrap_or_default(),
+        "py" | "pyw" | "pyx" => {
+            let mut parts = source_dirs(file, workspace_root, source_roots);
+            if stem != "__init__" {
+                parts.push(stem.to_string());
+            }
+            parts.join(".")
+        }
+        _ => stem.to_string(),
+    }
+}
+
+fn extension(file: &Path) -> &str {
+    file.extension().and_then(|e| e.to_str()).unwrap_or("")
+}
+
+fn is_php(ext: &str) -> bool {
+    matches!(
+        ext,
+        "php" | "phtml" | "php3" | "php4" | "php5" | "phps" | "phpt"
+    )
+}
+
+/// Directory components of `file` below the source root (or workspace root).
+/// None for a file outside the workspace root.
+fn source_dirs(file: &Path, workspace_root: Option<&Path>, source_roots: &[String]) -> Vec<String> {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let relative = match workspace_root.map(|root| dir.strip_prefix(root)) {
+        Some(Ok(relative)) => relative,
+        Some(Err(_)) => return Vec::new(),
+        None => dir,
+    };
+    let components: Vec<String> = relative
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(s) => s.to_str().map(str::to_string),
+            _ => None,
+        })
+        .collect();
+
+    for source_root in source_roots {
+        let root_parts: Vec<&str> = source_root.split('/').filter(|s| !s.is_empty()).collect();
+        if root_parts.is_empty() || root_parts.len() > components.len() {
+            continue;
+        }
+        // Last occurrence, so `app/src/main/java/...` in a multi-module build works
+        let found = (0..=components.len() - root_parts.len()).rev().find(|&i| {
+            components[i..i + root_parts.len()]
+                .iter()
+                .zip(&root_parts)
+                .all(|(a, b)| a == b)
+        });
+        if let Some(i) = found {
+            return components[i + root_parts.len()..].to_vec();
+        }
+    }
+
+    // No source root: only meaningful relative to a known workspace root
+    if workspace_root.is_some() {
+        components
+    } else {
+        Vec::new()
+    }
+}
+
+/// Go package names are the directory name, or `main` at a module root.
+fn go_package(file: &Path, dirs: &[String]) -> String {
+    let dir_name = dirs.last().cloned().or_else(|| {
+        file.parent()
+            .filter(|dir| !dir.join("go.mod").exists())
+            .and_then(|dir| dir.file_name())
+            .and_then(|n| n.to_str())
+            .map(str::to_string)
+    });
+    match dir_name {
+        Some(name) => name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect::<String>()
+            .to_lowercase(),
+        None => "main".to_string(),
+    }
+}
+
+/// Import path of the directory containing `file`: the `module` line of the
+/// nearest `go.mod` plus the path from there.
+fn go_module_path(file: &Path, workspace_root: Option<&Path>) -> Option<String> {
+    let dir = file.parent()?;
+    let mod_dir = nearest_ancestor_with(dir, "go.mod", workspace_root)?;
+    let content = std::fs::read_to_string(mod_dir.join("go.mod")).ok()?;
+    let module = content.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("module")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+            .map(|rest| rest.trim().trim_matches('"').to_string())
+    })?;
+
+    let relative = dir.strip_prefix(&mod_dir).ok()?;
+    let mut path = module;
+    for component in relative.iter().filter_map(|c| c.to_str()) {
+        path.push('/');
+        path.push_str(component);
+    }
+    Some(path)
+}
+
+/// PHP namespace from the `autoload` / `autoload-dev` PSR-4 maps of the
+/// workspace's `composer.json`; the longest matching directory wins.
+fn psr4_namespace(file: &Path, workspace_root: &Path) -> Option<String> {
+    let composer_dir =
+        nearest_ancestor_with(file.parent()?, "composer.json", Some(workspace_root))?;
+    let content = std::fs::read_to_string(composer_dir.join("composer.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let dir = file.parent()?.strip_prefix(&composer_dir).ok()?;
+
+    let mut best: Option<(usize, String)> = None;
+    for section in ["autoload", "autoload-dev"] {
+        let Some(map) = json[section]["psr-4"].as_object() else {
+            continue;
+        };
+        for (prefix, paths) in map {
+            // A PSR-4 entry maps to a single directory or a list of them
+            let paths: Vec<&str> = match paths {
+                serde_json::Value::String(p) => vec![p.as_str()],
+                serde_json::Value::Array(a) => a.iter().filter_map(|p| p.as_str()).collect(),
+                _ => continue,
+            };
+            for base in paths {
+                let base = Path::new(base.trim_end_matches('/'));
+                let Ok(rest) = dir.strip_prefix(base) else {
+                    continue;
+                };
+                let depth = base.components().count();
+                if best.as_ref().is_some_and(|(d, _)| *d > depth) {
+                    continue;
+                }
+                let mut ns = prefix.trim_end_matches('\\').to_string();
+                for component in rest.iter().filter_map(|c| c.to_str()) {
+                    if !ns.is_empty() {
+                        ns.push('\\');
+                    }
+                    ns.push_str(component);
+                }
+                best = Some((depth, ns));
+            }
+        }
+    }
+    best.map(|(_, ns)| ns)
+}
+
+/// Walk up from `dir` to the first directory containing `name`, not going
+/// above `stop` when one is given.
+fn nearest_ancestor_with(dir: &Path, name: &str, stop: Option<&Path>) -> Option<PathBuf> {
+    for ancestor in dir.ancestors() {
+        if ancestor.join(name).is_file() {
+            return Some(ancestor.to_path_buf());
+        }
+        if stop.is_some_and(|stop| ancestor == stop) {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Workspace;
+
+    fn roots() -> Vec<String> {
+        default_source_roots()
+    }
+
+    #[test]
+    fn java_package_is_relative_to_source_root() {
+        let root = Path::new("/ws");
+        let file = Path::new("/ws/app/src/main/java/com/example/util/Strings.java");
+        assert_eq!(package(file, Some(root), &roots()), "com.example.util");
+        assert_eq!(namespace(file, Some(root), &roots()), "com.example.util");
+    }
+
+    #[test]
+    fn kotlin_falls_back_to_plain_src_root() {
+        let file = Path::new("/ws/src/org/acme/Main.kt");
+        assert_eq!(package(file, Some(Path::new("/ws")), &roots()), "org.acme");
+    }
+
+    #[test]
+    fn configured_source_roots_take_priority() {
+        let file = Path::new("/ws/code/net/thing/A.scala");
+        let custom = vec!["code".to_string()];
+        assert_eq!(package(file, Some(Path::new("/ws")), &custom), "net.thing");
+    }
+
+    #[test]
+    fn without_source_root_uses_workspace_relative_dir() {
+        let file = Path::new("/ws/Foo/Bar/Baz.cs");
+        assert_eq!(namespace(file, Some(Path::new("/ws")), &roots()), "Foo.Bar");
+        assert_eq!(namespace(file, None, &roots()), "");
+    }
+
+    #[test]
+    fn files_outside_the_root_get_no_directories() {
+        let root = Some(Path::new("/ws"));
+        let file = Path::new("/home/user/tmp/Baz.cs");
+        assert_eq!(namespace(file, root, &roots()), "");
+        let file = Path::new("/home/user/tmp/util.py");
+        assert_eq!(module(file, root, &roots()), "util");
+    }
+
+    #[test]
+    fn cpp_namespace_uses_double_colons() {
+        let file = Path::new("/ws/src/engine/render/mesh.hpp");
+        assert_eq!(
+            namespace(file, Some(Path::new("/ws")), &roots()),
+            "engine::render"
+        );
+    }
+
+    #[test]
+    fn python_module_is_dotted_path_with_stem() {
+        let root = Some(Path::new("/ws"));
+        assert_eq!(
+            module(Path::new("/ws/src/pkg/sub/tool.py"), root, &roots()),
+            "pkg.sub.tool"
+        );
+        assert_eq!(
+            module(Path::new("/ws/src/pkg/__init__.py"), root, &roots()),
+            "pkg"
+        );
+    }
+
+    #[test]
+    fn verilog_module_is_file_stem() {
+        let file = Path::new("/ws/rtl/uart_tx.sv");
+        assert_eq!(module(file, Some(Path::new("/ws")), &roots()), "uart_tx");
+    }
+
+    #[test]
+    fn go_package_and_module_path_from_go_mod() {
+        let ws = Workspace::new("package-go");
+        ws.write("go.mod", "module github.com/acme/tool\n\ngo 1.22\n");
+        let nested = ws.write("internal/http-client/client.go", "");
+        let main = ws.write("main.go", "");
+
+        assert_eq!(package(&nested, Some(ws.root()), &roots()), "httpclient");
+        assert_eq!(
+            module(&nested, Some(ws.root()), &roots()),
+            "github.com/acme/tool/internal/http-client"
+        );
+        assert_eq!(package(&main, Some(ws.root()), &roots()), "main");
+        assert_eq!(
+            module(&main, Some(ws.root()), &roots()),
+            "github.com/acme/tool"
+        );
+    }
+
+    #[test]
+    fn php_namespace_from_psr4_map() {
+        let ws = Workspace::new("package-php");
+        ws.write(
+            "composer.json",
+            r#"{
+                "autoload": { "psr-4": { "App\\": "src/", "App\\Legacy\\": ["lib/legacy/"] } },
+                "autoload-dev": { "psr-4": { "App\\Tests\\": "tests/" } }
+            }"#,
+        );
+        let controller = ws.write("src/Http/Controllers/Home.php", "");
+        let legacy = ws.write("lib/legacy/Db.php", "");
+        let test = ws.write("tests/Unit/HomeTest.php", "");
+
+        assert_eq!(
+            namespace(&controller, Some(ws.root()), &roots()),
+            "App\\Http\\Controllers"
+        );
+        assert_eq!(namespace(&legacy, Some(ws.root()), &roots()), "App\\Legacy");
+        assert_eq!(
+            namespace(&test, Some(ws.root()), &roots()),
+            "App\\Tests\\Unit"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Dry-run rendering for the `auto-header.preview` command and the
+//! `render` subcommand: the header a file would get, plus the config files
+//! and template rule behind it, without editing any document.
+
+use crate::{
+    exclude, shebang, validate, Config, ConfigSource, Header, NamedProfile, UnresolvedPolicy,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct Preview {
+    pub path: String,
+    pub workspace_root: Option<String>,
+    /// Config files that were found, highest priority first. Files that
+    /// failed to parse are listed with the error and were not merged.
+    pub config_files: Vec<String>,
+    /// The setting that supplied the template, e.g.
+    /// `[header.by_extension.rs] template`.
+    pub template_rule: String,
+    /// The setting that supplied the body, if any.
+    pub body_rule: Option<String>,
+    /// The comment wrapper used for the file's extension.
+    pub profile: String,
+    /// The `#!` line the header starts with, if any.
+    pub shebang: Option<String>,
+    /// Whether the file would be made executable after the insert.
+    pub make_executable: bool,
+    /// Line endings and BOM, e.g. `CRLF + UTF-8 BOM`.
+    pub encoding: String,
+    /// Placeholders that didn't resolve, as written in the template.
+    pub unresolved: Vec<String>,
+    /// Why opening the file empty would not insert this header. The
+    /// `[insert]` rules depend on the editor session and aren't checked.
+    pub skip_reason: Option<String>,
+    /// Exactly what a plain-text insert writes, with tab stops replaced by
+    /// their defaults and prompts by their configured defaults.
+    pub text: String,
+    /// What snippet-capable clients receive instead, if there are tab stops.
+    pub snippet: Option<String>,
+}
+
+/// Render the header for `file` the same way `did_open` would.
+pub fn preview(file: &Path, workspace_root: Option<&Path>) -> Preview {
+    preview_sources(file, workspace_root, Config::sources(workspace_root))
+}
+
+/// `preview` with already-loaded `sources`, highest priority first.
+fn preview_sources(
+    file: &Path,
+    workspace_root: Option<&Path>,
+    sources: Vec<ConfigSource>,
+) -> Preview {
+    let mut config = Config::from_sources(&sources).resolve_env();
+    let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+    // A file holding just a shebang keeps it, as in `did_open`
+    if let Ok(content) = std::fs::read_to_string(file) {
+        if let Some(line) = shebang::existing(&content) {
+            config = config.with_shebang(ext, line);
+        }
+    }
+    let ext_config = config.header.by_extension.get(ext);
+
+    let template_rule = match ext_config {
+        Some(c) if c.template.as_deref().is_some_and(|t| !t.is_empty()) => {
+            format!("[header.by_extension.{}] template", ext)
+        }
+        Some(c) if c.profile.and_then(NamedProfile::template).is_some() => {
+            format!("[header.by_extension.{}] profile", ext)
+        }
+        _ => "[header] template".to_string(),
+    };
+    let body_rule = ext_config
+        .filter(|c| c.body.as_deref().is_some_and(|b| !b.is_empty()))
+        .map(|_| format!("[header.by_extension.{}] body", ext));
+
+    let file_path = file.to_str().unwrap_or("");
+    let (text, unresolved) = config.render_header(file_path, workspace_root, &HashMap::new());
+    let encoding = config.encoding_for(file);
+    let header = Header::new(encoding.apply(&text));
+
+    let skip_reason = if sources.is_empty() {
+        Some("no .auto-header.toml found".to_string())
+    } else if config.strict
+        && validate::validate_sources(&sources)
+            .iter()
+            .any(validate::Problem::is_error)
+    {
+        Some("strict = true and the config has errors (see `validate`)".to_string())
+    } else if !config.is_enabled_for(ext) {
+        Some("disabled by config".to_string())
+    } else {
+        exclude::skip_reason(&config.ignore, file, workspace_root)
+    }
+    .or_else(|| {
+        (config.header.unresolved == UnresolvedPolicy::Error && !unresolved.is_empty())
+            .then(|| "unresolved = \"error\" and placeholders didn't resolve".to_string())
+    });
+
+    Preview {
+        path: file.display().to_string(),
+        workspace_root: workspace_root.map(|root| root.display().to_string()),
+        config_files: sources.iter().map(ConfigSource::describe).collect(),
+        template_rule,
+        body_rule,
+        profile: config.profile_for(ext).describe(),
+        shebang: config.shebang_for(file, workspace_root),
+        make_executable: config.make_executable_for(file, workspace_root),
+        encoding: encoding.describe(),
+        unresolved,
+        skip_reason,
+        text: header.text,
+        snippet: header.snippet,
+    }
+}
+
+/// Everything except the header text, one `Key: value` per line.
+impl fmt::Display for Preview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Path: {}", self.path)?;
+        writeln!(
+            f,
+            "Workspace root: {}",
+            self.workspace_root.as_deref().unwrap_or("(none)")
+        )?;
+        if self.config_files.is_empty() {
+            writeln!(f, "Config files: (none, using built-in defaults)")?;
+        } else {
+            writeln!(f, "Config files:")?;
+            for file in &self.config_files {
+                writeln!(f, "  {}", file)?;
+            }
+        }
+        writeln!(f, "Template: {}", self.template_rule)?;
+        if let Some(body) = &self.body_rule {
+            writeln!(f, "Body: {}", body)?;
+        }
+        writeln!(f, "Profile: {}", self.profile)?;
+        if let Some(shebang) = &self.shebang {
+            writeln!(f, "Shebang: {}", shebang)?;
+        }
+        if self.make_executable {
+            writeln!(f, "Make executable: yes")?;
+        }
+        writeln!(f, "Encoding: {}", self.encoding)?;
+        if !self.unresolved.is_empty() {
+            writeln!(f, "Unresolved: {}", self.unresolved.join(", "))?;
+        }
+        if let Some(reason) = &self.skip_reason {
+            writeln!(f, "Would skip: {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{project_sources, Workspace};
+
+    /// `preview` reading only the workspace's own config.
+    fn preview_in(file: &Path, ws: &Workspace) -> Preview {
+        preview_sources(file, Some(ws.root()), project_sources(ws.root()))
+    }
+
+    #[test]
+    fn preview_reports_rule_profile_and_project_config() {
+        let ws = Workspace::new("preview-rule");
+        ws.write(
+            ".auto-header.toml",
+            r#"
+[header]
+template = "Generic {filename}"
+
+[header.by_extension.rs]
+template = "Rust {filename}"
+body = "fn main() {}\n"
+"#,
+        );
+        let file = ws.root().join("src/main.rs");
+
+        let preview = preview_in(&file, &ws);
+        assert!(preview.config_files[0].ends_with(".auto-header.toml (project)"));
+        assert_eq!(preview.template_rule, "[header.by_extension.rs] template");
+        assert_eq!(
+            preview.body_rule.as_deref(),
+            Some("[header.by_extension.rs] body")
+        );
+        assert_eq!(preview.profile, "block comment /* */");
+        assert_eq!(preview.encoding, "LF");
+        assert_eq!(preview.skip_reason, None);
+        assert_eq!(preview.text, "/*\n * Rust main.rs\n */\n\nfn main() {}\n");
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn preview_explains_why_a_file_would_be_skipped() {
+        let ws = Workspace::new("preview-skip");
+        ws.write(".auto-header.toml", "[ignore]\npatterns = [\"gen/\"]\n");
+        let file = ws.root().join("gen/out.py");
+
+        let preview = preview_in(&file, &ws);
+        assert_eq!(preview.template_rule, "[header] template");
+        assert_eq!(
+            preview.profile,
+            "block comment \"\"\" \"\"\" after `# -*- coding: utf-8 -*-`"
+        );
+        assert!(preview.skip_reason.unwrap().contains("gen/"));
+    }
+
+    #[test]
+    fn strict_mode_only_blocks_on_errors() {
+        let ws = Workspace::new("preview-strict");
+        ws.write(
+            ".auto-header.toml",
+            "strict = true\n[header.by_extension.json]\nenabled = false\n",
+        );
+        let file = ws.root().join("main.rs");
+        assert_eq!(preview_in(&file, &ws).skip_reason, None);
+
+        ws.write(".auto-header.toml", "strict = true\n[headr]\n");
+        assert!(preview_in(&file, &ws)
+            .skip_reason
+            .unwrap()
+            .contains("errors"));
+    }
+
+    #[test]
+    fn unparsable_config_is_listed_but_not_merged() {
+        let ws = Workspace::new("preview-broken");
+        ws.write(".auto-header.toml", "[author\nname = \"x\"");
+        let preview = preview_in(&ws.root().join("a.sh"), &ws);
+        assert!(preview.config_files[0].contains("(project, ignored: line 1: "));
+        assert!(preview.to_string().contains("Profile: line comment #"));
+    }
+
+    #[test]
+    fn existing_shebang_is_kept() {
+        let ws = Workspace::new("preview-shebang");
+        ws.write(
+            ".auto-header.toml",
+            "[header]\ntemplate = \"{interpreter}\"\n[header.by_extension.sh]\nmake_executable = true\n",
+        );
+        let file = ws.write("run.sh", "#!/bin/bash -eu\n");
+        let preview = preview_in(&file, &ws);
+        assert_eq!(preview.shebang.as_deref(), Some("#!/bin/bash -eu"));
+        assert_eq!(preview.text, "#!/bin/bash -eu\n#\n# bash\n#\n\n");
+        let shown = preview.to_string();
+        assert!(shown.contains("Shebang: #!/bin/bash -eu\nMake executable: yes\n"));
+    }
+
+    #[test]
+    fn unresolved_placeholders_are_listed_and_can_block_insertion() {
+        let ws = Workspace::new("preview-unresolved");
+        ws.write(
+            ".auto-header.toml",
+            "[header]\ntemplate = \"{filename} {typo}\"\nunresolved = \"error\"\n",
+        );
+        let preview = preview_in(&ws.root().join("a.sh"), &ws);
+        assert_eq!(preview.unresolved, vec!["{typo}"]);
+        assert!(preview.to_string().contains("Unresolved: {typo}\n"));
+        assert!(preview.skip_reason.unwrap().contains("unresolved"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Line wrapping for header content, applied before the comment markers are
+//! added so that the finished lines fit the project's width limit.
+//!
+//! `.editorconfig` `max_line_length` only breaks lines that are too long;
+//! `wrap_width` reflows whole paragraphs (see `reflow`). Neither splits a
+//! `${1:tab stop}`, and spacing inside a line is kept.
+
+use crate::snippet;
+
+/// Break every line of `text` longer than `width` characters at spaces.
+/// Continuation lines keep the original line's indentation; a word that is
+/// longer than the width on its own (a URL, say) is never split.
+pub fn wrap_long_lines(text: &str, width: usize) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        if line.chars().count() <= width {
+            out.push_str(line);
+            out.push('\n');
+        } else {
+            push_wrapped(&mut out, indent, indent, &[line], width);
+        }
+    }
+    out
+}
+
+/// Refill every paragraph of `text` that has a line longer than `width`
+/// characters. Paragraphs end at blank lines; `Key: value` fields and list
+/// items (`-`, `*`, `1.`) start their own and wrap with a hanging indent.
+/// Indented lines are code or quotes and are kept as they are, and words
+/// longer than the width (URLs) are never split.
+pub fn reflow(text: &str, width: usize) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        let indented = line.starts_with([' ', '\t']);
+        if line.trim().is_empty() || indented || hanging_indent(line).is_some() {
+            flush(&mut out, &mut paragraph, width);
+        }
+        if line.trim().is_empty() || indented {
+            out.push_str(line);
+            out.push('\n');
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut out, &mut paragraph, width);
+    out
+}
+
+fn flush(out: &mut String, paragraph: &mut Vec<&str>, width: usize) {
+    let Some(first) = paragraph.first() else {
+        return;
+    };
+    if paragraph.iter().all(|line| line.chars().count() <= width) {
+        for line in paragraph.iter() {
+            out.push_str(line);
+            out.push('\n');
+        }
+    } else {
+        let hanging = " ".repeat(
+            hanging_indent(first)
+                .filter(|n| *n < width / 2)
+                .unwrap_or(0),
+        );
+        push_wrapped(out, "", &hanging, paragraph, width);
+    }
+    paragraph.clear();
+}
+
+/// Width of the marker of a list item (`- `, `1. `) or the key of a
+/// `Key: value` field, if `line` starts with one.
+fn hanging_indent(line: &str) -> Option<usize> {
+    let (head, _) = line.split_once(' ')?;
+    let list_item = matches!(head, "-" | "*" | "+")
+        || head
+            .strip_suffix(['.', ')'])
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    if list_item {
+        return Some(head.chars().count() + 1);
+    }
+    // A field key is a single word followed by `: `
+    let (key, _) = line.split_once(": ")?;
+    let field = key.chars().next().is_some_and(char::is_alphabetic)
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_'));
+    field.then(|| key.chars().count() + 2)
+}
+
+/// Greedily fill lines of at most `width` characters (including the
+/// indent) with the words of `lines`: the first line gets `first_indent`,
+/// the rest `indent`. Spacing between words on the same input line is kept;
+/// words from the next line follow after one space. Every line ends in `\n`.
+fn push_wrapped(out: &mut String, first_indent: &str, indent: &str, lines: &[&str], width: usize) {
+    let mut line_indent = first_indent;
+    let mut current = String::new();
+    let mut current_width = 0;
+    let words = lines.iter().flat_map(|line| {
+        words(line)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (gap, word))| (if i == 0 { " " } else { gap }, word))
+    });
+    for (gap, word) in words {
+        let word_width = word.chars().count();
+        let gap_width = gap.chars().count();
+        let indent_width = line_indent.chars().count();
+        if current_width > 0 && indent_width + current_width + gap_width + word_width > width {
+            out.push_str(line_indent);
+            out.push_str(&current);
+            out.push('\n');
+            line_indent = indent;
+            current.clear();
+            current_width = 0;
+        }
+        if current_width > 0 {
+            current.push_str(gap);
+            current_width += gap_width;
+        }
+        current.push_str(word);
+        current_width += word_width;
+    }
+    out.push_str(line_indent);
+    out.push_str(&current);
+    out.push('\n');
+}
+
+/// The words of `line`, each with the whitespace before it. A tab stop is
+/// one word even if its default has spaces, and so is an escaped one
+/// (`$${1:two words}`).
+fn words(line: &str) -> Vec<(&str, &str)> {
+    let mut words = Vec::new();
+    let mut rest = line;
+    loop {
+        let tail = rest.trim_start();
+        if tail.is_empty() {
+            return words;
+        }
+        let gap = &rest[..rest.len() - tail.len()];
+        let mut len = 0;
+        while len < tail.len() {
+            let at = &tail[len..];
+            if at.starts_with(char::is_whitespace) {
+                break;
+            }
+            len += match at.strip_prefix('$').and_then(snippet::tab_stop_len) {
+                Some(escaped) => 1 + escaped,
+                None => snippet::tab_stop_len(at)
+                    .unwrap_or_else(|| at.chars().next().map_or(1, char::len_utf8)),
+            };
+        }
+        let (word, after) = tail.split_at(len);
+        words.push((gap, word));
+        rest = after;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_lines_break_at_spaces_and_keep_indentation() {
+        let text = "short\n  one two three four five\n";
+        assert_eq!(
+            wrap_long_lines(text, 12),
+            "short\n  one two\n  three four\n  five\n"
+        );
+    }
+
+    #[test]
+    fn overlong_words_stay_whole() {
+        let text = "see https://example.com/a/very/long/path now";
+        assert_eq!(
+            wrap_long_lines(text, 10),
+            "see\nhttps://example.com/a/very/long/path\nnow\n"
+        );
+    }
+
+    #[test]
+    fn blank_lines_survive() {
+        assert_eq!(wrap_long_lines("a\n\nb", 5), "a\n\nb\n");
+    }
+
+    #[test]
+    fn overflowing_paragraphs_are_refilled() {
+        let text = "Licensed under the Apache License, Version 2.0 (the\n\"License\"); you may not use this file except\nin compliance.\n\nShort\nlines stay.\n";
+        assert_eq!(
+            reflow(text, 30),
+            "Licensed under the Apache\nLicense, Version 2.0 (the\n\"License\"); you may not use\nthis file except in\ncompliance.\n\nShort\nlines stay.\n"
+        );
+    }
+
+    #[test]
+    fn fields_list_items_and_indented_blocks_keep_their_shape() {
+        let text = "File: a.rs\nDescription: parses the configuration file format\n- first item that is rather long\n    indented code that is far too long to fit\nSee https://example.com/licenses/apache-2.0.txt\n";
+        assert_eq!(
+            reflow(text, 30),
+            "File: a.rs\n\
+             Description: parses the\n             configuration\n             file format\n\
+             - first item that is rather\n  long\n\
+             \x20   indented code that is far too long to fit\n\
+             See\nhttps://example.com/licenses/apache-2.0.txt\n"
+        );
+    }
+
+    #[test]
+    fn tab_stops_stay_whole_and_spacing_is_kept() {
+        let text = "See ${1:a multi word default} and ${2:more}\nx  =  1 is aligned  here\n";
+        assert_eq!(
+            reflow(text, 20),
+            "See\n${1:a multi word default}\nand ${2:more} x  =\n1 is aligned  here\n"
+        );
+        assert_eq!(
+            wrap_long_lines("echo $${1:x y} ok", 12),
+            "echo\n$${1:x y} ok\n"
+        );
+    }
+
+    #[test]
+    fn only_known_shapes_count_as_fields() {
+        assert_eq!(hanging_indent("Author: Alice"), Some(8));
+        assert_eq!(hanging_indent("12. twelfth"), Some(4));
+        assert_eq!(hanging_indent("Note that: this is prose"), None);
+        assert_eq!(hanging_indent("See https://x.org"), None);
+        assert_eq!(hanging_indent("(c) 2026: Acme"), None);
+    }
+}
@@ -0,0 +1,507 @@
+//! JSON Schema for `.auto-header.toml`, generated from the `PartialConfig`
+//! structs, and the completion and hover offered while editing the file.
+//!
+//! Completion and hover read the schema too, so a new config field only
+//! needs a doc comment on its `Partial*` struct to show up in the editor.
+
+use crate::{HeaderProfile, PartialConfig, TEMPLATE_VARIABLES};
+use schemars::gen::SchemaSettings;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::OnceLock;
+use tower_lsp::lsp_types::*;
+
+/// File names the server treats as its own config.
+const CONFIG_FILE_NAMES: &[&str] = &[".auto-header.toml", "auto-header.toml"];
+
+pub fn is_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| CONFIG_FILE_NAMES.contains(&name))
+}
+
+/// The schema as JSON. TOML has no null, so optional fields are plain types.
+/// Built once, since completion and hover read it on every request.
+pub fn json_schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        let settings = SchemaSettings::draft07().with(|s| s.option_add_null_type = false);
+        let mut schema = settings
+            .into_generator()
+            .into_root_schema_for::<PartialConfig>();
+        schema.schema.metadata().title = Some(".auto-header.toml".to_string());
+        serde_json::to_value(schema).unwrap_or_default()
+    })
+}
+
+/// Follow `$ref`s and single-schema `allOf`/`anyOf` wrappers.
+pub fn resolve<'a>(root: &'a Value, mut node: &'a Value) -> &'a Value {
+    loop {
+        if let Some(name) = node["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/definitions/"))
+        {
+            node = &root["definitions"][name];
+        } else if let Some([only]) = node["allOf"].as_array().map(Vec::as_slice) {
+            node = only;
+        } else {
+            return node;
+        }
+    }
+}
+
+/// The schema of the value at `path`. Also returns the description found
+/// on the way in.
+fn lookup<'a>(root: &'a Value, path: &[String]) -> Option<(&'a Value, Option<&'a str>)> {
+    let mut node = resolve(root, root);
+    let mut description = node["description"].as_str();
+    for key in path {
+        let next = entry(node, key)?;
+        node = child(root, node, key)?;
+        description = next["description"]
+            .as_str()
+            .or(node["description"].as_str());
+    }
+    Some((node, description))
+}
+
+/// The raw schema entry for `key` in table schema `node`: a named property,
+/// or a map entry (`by_extension.<ext>`, `prompts.<label>`).
+fn entry<'a>(node: &'a Value, key: &str) -> Option<&'a Value> {
+    node["properties"].get(key).or_else(|| {
+        node["additionalProperties"]
+            .as_object()
+            .map(|_| &node["additionalProperties"])
+    })
+}
+
+/// The resolved schema for `key` in table schema `node`, unwrapping arrays
+/// of tables (`[[authors]]`) to their item. `None` for unknown keys.
+pub fn child<'a>(root: &'a Value, node: &'a Value, key: &str) -> Option<&'a Value> {
+    let node = resolve(root, entry(node, key)?);
+    if node["type"] == "array" && node["items"].is_object() {
+        return Some(resolve(root, &node["items"]));
+    }
+    Some(node)
+}
+
+/// Named keys of table schema `node`.
+pub fn property_names(node: &Value) -> Vec<&str> {
+    node["properties"]
+        .as_object()
+        .map(|props| props.keys().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn is_table(node: &Value) -> bool {
+    node["type"] == "object"
+}
+
+/// A name and its description from the schema.
+type Documented = (String, Option<String>);
+
+/// Every `[table]` path in the schema, with `<ext>` expanded to the known
+/// extensions. Array tables (`[[authors]]`) are returned separately.
+fn table_paths(root: &Value) -> (Vec<Documented>, Vec<Documented>) {
+    let mut tables = Vec::new();
+    let mut arrays = Vec::new();
+    let Some(properties) = resolve(root, root)["properties"].as_object() else {
+        return (tables, arrays);
+    };
+    for (key, prop) in properties {
+        let description = prop["description"].as_str().map(str::to_string);
+        let node = resolve(root, prop);
+        if node["type"] == "array" && is_table(resolve(root, &node["items"])) {
+            arrays.push((key.clone(), description));
+        } else if is_table(node) {
+            tables.push((key.clone(), description));
+            let Some(children) = node["properties"].as_object() else {
+                continue;
+            };
+            for (child, child_prop) in children {
+                let child_node = resolve(root, child_prop);
+                if !is_table(child_node) {
+                    continue;
+                }
+                let path = format!("{}.{}", key, child);
+                let child_description = child_prop["description"].as_str().map(str::to_string);
+                tables.push((path.clone(), child_description.clone()));
+                if child == "by_extension" {
+                    for ext in HeaderProfile::KNOWN_EXTENSIONS {
+                        tables.push((format!("{}.{}", path, ext), child_description.clone()));
+                    }
+                }
+            }
+        }
+    }
+    (tables, arrays)
+}
+
+/// Where the cursor is, as far as completion and hover care.
+#[derive(Debug, PartialEq)]
+enum Context {
+    /// Inside `[...]` / `[[...]]`; `typed` is the path so far.
+    Section {
+        typed: String,
+        array: bool,
+    },
+    /// At the start of a `key = value` line in table `table`.
+    Key {
+        table: Vec<String>,
+        typed: String,
+    },
+    /// Inside `{...` in a string; `typed` is the text after the brace.
+    Placeholder {
+        typed: String,
+    },
+    Other,
+}
+
+/// Byte offset in `line` of the UTF-16 column `character`.
+fn byte_offset(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= character as usize {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+fn context(text: &str, position: Position) -> Context {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let Some(line) = lines.get(position.line as usize) else {
+        return Context::Other;
+    };
+    let line = line.trim_end_matches('\r');
+    let before = &line[..byte_offset(line, position.character)];
+    let earlier = &lines[..position.line as usize];
+
+    if in_string(earlier, before) {
+        return match before.rfind('{') {
+            Some(open) if !before[open..].contains('}') => Context::Placeholder {
+                typed: before[open + 1..].to_string(),
+            },
+            _ => Context::Other,
+        };
+    }
+
+    let trimmed = before.trim_start();
+    if let Some(rest) = trimmed.strip_prefix('[') {
+        if !rest.contains(']') {
+            let array = rest.starts_with('[');
+            let typed = rest.trim_start_matches('[').trim_start().to_string();
+            return Context::Section { typed, array };
+        }
+        return Context::Other;
+    }
+    if trimmed
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Context::Key {
+            table: current_table(earlier),
+            typed: trimmed.to_string(),
+        };
+    }
+    Context::Other
+}
+
+/// Whether the cursor is inside a string value: an open `"""`/`'''` from an
+/// earlier line, or an unclosed quote after `=` on this one.
+fn in_string(earlier: &[&str], before: &str) -> bool {
+    let preceding: String = earlier.join("\n");
+    for delimiter in ["\"\"\"", "'''"] {
+        if preceding.matches(delimiter).count() % 2 == 1 {
+            return !before.contains(delimiter);
+        }
+    }
+    let Some((_, value)) = before.split_once('=') else {
+        return false;
+    };
+    let mut open = None;
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (open, c) {
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => open = None,
+            (None, '"' | '\'') => open = Some(c),
+            _ => {}
+        }
+    }
+    open.is_some()
+}
+
+/// Path of the last `[table]` header before the cursor.
+fn current_table(earlier: &[&str]) -> Vec<String> {
+    earlier
+        .iter()
+        .rev()
+        .find_map(|line| {
+            let line = line.trim();
+            let inner = line.strip_prefix('[')?.trim_start_matches('[');
+            let inner = inner.split(']').next()?;
+            Some(split_key(inner))
+        })
+        .unwrap_or_default()
+}
+
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn documentation(text: &str) -> Documentation {
+    Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: text.to_string(),
+    })
+}
+
+fn item(label: &str, kind: CompletionItemKind, doc: Option<&str>) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        documentation: doc.map(documentation),
+        ..Default::default()
+    }
+}
+
+/// Completion items for `position` in a config file.
+pub fn completions(text: &str, position: Position) -> Vec<CompletionItem> {
+    let root = json_schema();
+    match context(text, position) {
+        Context::Section { typed, array } => {
+            let (tables, arrays) = table_paths(root);
+            let candidates = if array { arrays } else { tables };
+            // Replace the whole dotted path typed so far, not just the last word
+            let start = Position {
+                line: position.line,
+                character: position.character - typed.encode_utf16().count() as u32,
+            };
+            candidates
+                .into_iter()
+                .filter(|(path, _)| path.starts_with(&typed))
+                .map(|(path, doc)| CompletionItem {
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                        range: Range {
+                            start,
+                            end: position,
+                        },
+                        new_text: path.clone(),
+                    })),
+                    ..item(&path, CompletionItemKind::MODULE, doc.as_deref())
+                })
+                .collect()
+        }
+        Context::Key { table, typed } => {
+            let Some((node, _)) = lookup(root, &table) else {
+                return Vec::new();
+            };
+            let Some(properties) = node["properties"].as_object() else {
+                return Vec::new();
+            };
+            properties
+                .iter()
+                .filter(|(key, prop)| key.starts_with(&typed) && !is_table(resolve(root, prop)))
+                .map(|(key, prop)| CompletionItem {
+                    insert_text: Some(format!("{} = ", key)),
+                    ..item(
+                        key,
+                        CompletionItemKind::PROPERTY,
+                        prop["description"].as_str(),
+                    )
+                })
+                .collect()
+        }
+        Context::Placeholder { typed } => {
+            if typed.contains('|') {
+                crate::template::FILTERS
+                    .iter()
+                    .map(|(name, doc)| item(name, CompletionItemKind::FUNCTION, Some(doc)))
+                    .collect()
+            } else {
+                TEMPLATE_VARIABLES
+                    .iter()
+                    .chain(SPECIAL_PLACEHOLDERS)
+                    .map(|(name, doc)| item(name, CompletionItemKind::VARIABLE, Some(doc)))
+                    .collect()
+            }
+        }
+        Context::Other => Vec::new(),
+    }
+}
+
+/// Placeholders that aren't plain variables. Names ending in `:` take an
+/// argument after the colon.
+pub const SPECIAL_PLACEHOLDERS: &[(&str, &str)] = &[
+    (
+        "env:",
+        "`{env:VAR}` or `{env:VAR:-fallback}`: an environment variable",
+    ),
+    (
+        "prompt:",
+        "`{prompt:Label}`: asked for when the header is inserted; see `[prompts.Label]`",
+    ),
+    (
+        "cursor",
+        "Where to put the caret after inserting (body only)",
+    ),
+];
+
+/// Hover documentation for the key, section or placeholder at `position`.
+pub fn hover(text: &str, position: Position) -> Option<Hover> {
+    let line = text.split('\n').nth(position.line as usize)?;
+    let line = line.trim_end_matches('\r');
+    let offset = byte_offset(line, position.character);
+    let is_word = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':');
+    let start = line[..offset]
+        .rfind(|c: char| !is_word(c))
+        .map_or(0, |i| i + 1);
+    let end = line[offset..]
+        .find(|c: char| !is_word(c))
+        .map_or(line.len(), |i| offset + i);
+    let word = &line[start..end];
+    if word.is_empty() {
+        return None;
+    }
+
+    // Classify by what precedes the word, so hovering mid-word works
+    let word_start = Position {
+        line: position.line,
+        character: line[..start].encode_utf16().count() as u32,
+    };
+    let doc = match context(text, word_start) {
+        Context::Section { typed, .. } => {
+            let mut path = split_key(&typed);
+            path.push(word.to_string());
+            let root = json_schema();
+            lookup(root, &path).and_then(|(_, d)| d.map(str::to_string))
+        }
+        Context::Key { table, .. } => {
+            let mut path = table;
+            path.push(word.to_string());
+            let root = json_schema();
+            lookup(root, &path).and_then(|(_, d)| d.map(str::to_string))
+        }
+        Context::Placeholder { typed } => {
+            let table: &[(&str, &str)] = if typed.contains('|') {
+                crate::template::FILTERS
+            } else {
+                TEMPLATE_VARIABLES
+            };
+            let name = word.split_once(':').map_or(word, |(prefix, _)| prefix);
+            table
+                .iter()
+                .chain(SPECIAL_PLACEHOLDERS)
+                .find(|(n, _)| n.trim_end_matches(':') == name)
+                .map(|(n, d)| format!("`{{{}}}`: {}", n.trim_end_matches(':'), d))
+        }
+        Context::Other => None,
+    }?;
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: doc,
+        }),
+        range: Some(Range {
+            start: word_start,
+            end: Position {
+                line: position.line,
+                character: line[..end].encode_utf16().count() as u32,
+            },
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    #[test]
+    fn checked_in_schema_is_up_to_date() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../auto-header.schema.json");
+        let checked_in = std::fs::read_to_string(path).unwrap();
+        let generated = serde_json::to_string_pretty(json_schema()).unwrap() + "\n";
+        assert!(
+            checked_in == generated,
+            "auto-header.schema.json is stale; regenerate it with `auto-header-server schema`"
+        );
+    }
+
+    #[test]
+    fn schema_rejects_unknown_keys_and_has_no_nulls() {
+        let schema = json_schema();
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["title"], ".auto-header.toml");
+        assert!(!schema.to_string().contains("null\""));
+    }
+
+    #[test]
+    fn section_names_and_extensions_complete() {
+        let items = completions("[head", at(0, 5));
+        assert!(labels(&items).contains(&"header.by_extension.rs"));
+        assert!(!labels(&items).contains(&"author"));
+
+        let items = completions("[[", at(0, 2));
+        assert_eq!(labels(&items), vec!["authors"]);
+    }
+
+    #[test]
+    fn keys_complete_for_the_current_table() {
+        let text = "[author]\nname = \"x\"\n\n[header.by_extension.rs]\n";
+        let items = completions(text, at(4, 0));
+        let keys = labels(&items);
+        for key in ["body", "enabled", "template", "wrap_width"] {
+            assert!(keys.contains(&key), "{:?}", keys);
+        }
+        assert!(!keys.contains(&"author"));
+
+        let items = completions("[ignore]\ng", at(1, 1));
+        assert_eq!(labels(&items), vec!["gitignore"]);
+    }
+
+    #[test]
+    fn placeholders_and_filters_complete_inside_templates() {
+        let text = "[header]\ntemplate = \"\"\"\nFile: {fi";
+        let items = completions(text, at(2, 9));
+        assert!(labels(&items).contains(&"filename"));
+        assert!(labels(&items).contains(&"env:"));
+
+        let text = "template = \"{filename|";
+        let items = completions(text, at(0, 22));
+        assert!(labels(&items).contains(&"upper"));
+
+        // Outside a string a `{` means nothing
+        assert!(completions("x = 1 # {", at(0, 9)).is_empty());
+    }
+
+    #[test]
+    fn hover_documents_keys_sections_and_placeholders() {
+        let text = "[insert]\nonly_created = true\n\n[header]\ntemplate = \"{owners|upper}\"\n";
+        let value = |hover: Option<Hover>| match hover.unwrap().contents {
+            HoverContents::Markup(m) => m.value,
+            _ => unreachable!(),
+        };
+
+        assert!(value(hover(text, at(1, 3))).contains("created this session"));
+        assert!(value(hover(text, at(0, 3))).contains("Which empty files"));
+        assert!(value(hover(text, at(4, 15))).contains("CODEOWNERS"));
+        assert!(value(hover(text, at(4, 22))).contains("UPPER"));
+        assert!(hover(text, at(2, 0)).is_none());
+    }
+}
@@ -0,0 +1,199 @@
+//! Shebang lines: building them from the `shebang` setting or the
+//! extension's interpreter, reading them back from files, and making
+//! script files executable.
+
+use std::path::Path;
+
+/// The interpreter `{interpreter}` and the default shebang use for `ext`;
+/// empty if the extension isn't a script language.
+pub fn default_interpreter(ext: &str) -> &'static str {
+    match ext {
+        "py" | "pyw" | "pyx" => "python3",
+        "rb" => "ruby",
+        "pl" | "pm" => "perl",
+        "sh" => "sh",
+        "bash" => "bash",
+        "zsh" => "zsh",
+        "fish" => "fish",
+        "r" | "R" => "Rscript",
+        "jl" => "julia",
+        _ => "",
+    }
+}
+
+/// Directories whose files are run directly, so files there with an
+/// interpreter (Python) get a shebang even without a script profile.
+const SCRIPT_DIRS: &[&str] = &["bin", "scripts"];
+
+/// Whether `file` sits in a `bin/` or `scripts/` directory: anywhere below
+/// `workspace_root`, or directly in one when the file is outside it or
+/// there is no root.
+pub fn in_script_dir(file: &Path, workspace_root: Option<&Path>) -> bool {
+    let is_script_dir = |name: &std::ffi::OsStr| SCRIPT_DIRS.iter().any(|d| name == *d);
+    match workspace_root.and_then(|root| file.strip_prefix(root).ok()) {
+        Some(relative) => relative
+            .parent()
+            .is_some_and(|dir| dir.components().any(|c| is_script_dir(c.as_os_str()))),
+        None => file
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(is_script_dir),
+    }
+}
+
+/// The `#!` line for a `shebang` setting, or `None` for `"none"`/`""`.
+///
+/// - `#!...` is used as written
+/// - an absolute path runs directly: `/bin/sh` → `#!/bin/sh`
+/// - a program goes through `env`: `bash` → `#!/usr/bin/env bash`
+/// - a program with arguments needs `env -S` to split them:
+///   `deno run -A` → `#!/usr/bin/env -S deno run -A`
+pub fn line(spec: &str) -> Option<String> {
+    let spec = spec.trim();
+    if spec.is_empty() || spec == "none" {
+        None
+    } else if spec.starts_with("#!") {
+        Some(spec.to_string())
+    } else if spec.starts_with('/') {
+        Some(format!("#!{}", spec))
+    } else if spec.contains(char::is_whitespace) {
+        Some(format!("#!/usr/bin/env -S {}", spec))
+    } else {
+        Some(format!("#!/usr/bin/env {}", spec))
+    }
+}
+
+/// The program a shebang line runs, skipping `env` and its options:
+/// `#!/usr/bin/env -S deno run` → `deno`, `#!/bin/sh -e` → `sh`.
+pub fn interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    if program != "env" {
+        return Some(program);
+    }
+    words.find(|word| !word.starts_with('-') && !word.contains('='))
+}
+
+/// The shebang of a file whose only content is a `#!` line on line 1, so a
+/// header can still go in below it. `#![...]` is a Rust inner attribute,
+/// and a shebang must name a program.
+pub fn existing(content: &str) -> Option<&str> {
+    let content = content.trim_end();
+    let shebang = content.starts_with("#!")
+        && !content.starts_with("#![")
+        && !content.contains('\n')
+        && interpreter(content).is_some();
+    shebang.then_some(content)
+}
+
+/// Add execute permission wherever `path` has read permission, like
+/// `chmod +x` under the usual umask. Returns whether the mode changed.
+#[cfg(unix)]
+pub fn make_executable(path: &Path) -> std::io::Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    let mode = permissions.mode();
+    let executable = mode | (mode & 0o444) >> 2;
+    if executable == mode {
+        return Ok(false);
+    }
+    permissions.set_mode(executable);
+    std::fs::set_permissions(path, permissions)?;
+    Ok(true)
+}
+
+/// Files have no executable bit outside Unix.
+#[cfg(not(unix))]
+pub fn make_executable(_path: &Path) -> std::io::Result<bool> {
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_become_shebang_lines() {
+        assert_eq!(line("bash").as_deref(), Some("#!/usr/bin/env bash"));
+        assert_eq!(line("/bin/sh").as_deref(), Some("#!/bin/sh"));
+        assert_eq!(
+            line("deno run --allow-read").as_deref(),
+            Some("#!/usr/bin/env -S deno run --allow-read")
+        );
+        assert_eq!(
+            line("#!/usr/bin/python3 -u").as_deref(),
+            Some("#!/usr/bin/python3 -u")
+        );
+        assert_eq!(line("none"), None);
+        assert_eq!(line(""), None);
+    }
+
+    #[test]
+    fn interpreter_skips_env_and_its_options() {
+        assert_eq!(interpreter("#!/usr/bin/env bash"), Some("bash"));
+        assert_eq!(interpreter("#!/usr/bin/env -S deno run -A"), Some("deno"));
+        assert_eq!(interpreter("#!/usr/bin/env -i PATH=/bin sh"), Some("sh"));
+        assert_eq!(interpreter("#!/bin/sh -e"), Some("sh"));
+        assert_eq!(interpreter("#!"), None);
+        assert_eq!(interpreter("# not a shebang"), None);
+    }
+
+    #[test]
+    fn script_dirs_count_below_the_workspace_root() {
+        let root = Path::new("/home/me/scripts/app");
+        assert!(in_script_dir(
+            Path::new("/home/me/scripts/app/bin/run.py"),
+            Some(root)
+        ));
+        assert!(in_script_dir(
+            Path::new("/home/me/scripts/app/tools/scripts/x.py"),
+            Some(root)
+        ));
+        assert!(!in_script_dir(
+            Path::new("/home/me/scripts/app/src/lib.py"),
+            Some(root)
+        ));
+        assert!(!in_script_dir(
+            Path::new("/home/me/scripts/app/bin.py"),
+            Some(root)
+        ));
+        // Without a root only the file's own directory counts
+        assert!(in_script_dir(Path::new("/w/bin/run.py"), None));
+        assert!(!in_script_dir(
+            Path::new("/home/me/scripts/proj/src/util.py"),
+            None
+        ));
+        assert!(!in_script_dir(
+            Path::new("/home/me/scripts/proj/src/util.py"),
+            Some(Path::new("/elsewhere"))
+        ));
+    }
+
+    #[test]
+    fn only_a_lone_shebang_counts_as_existing() {
+        assert_eq!(existing("#!/bin/zsh\n"), Some("#!/bin/zsh"));
+        assert_eq!(existing("#!/bin/zsh  \n\n"), Some("#!/bin/zsh"));
+        assert_eq!(existing("\n#!/bin/zsh\n"), None);
+        assert_eq!(existing("#![no_std]\n"), None);
+        assert_eq!(existing("#!\n"), None);
+        assert_eq!(existing("#!/bin/zsh\necho hi\n"), None);
+        assert_eq!(existing(""), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn make_executable_adds_x_where_readable() {
+        use crate::test_support::Workspace;
+        use std::os::unix::fs::PermissionsExt;
+
+        let ws = Workspace::new("shebang-chmod");
+        let file = ws.write("run.sh", "#!/bin/sh\n");
+        let mode = |file: &Path| std::fs::metadata(file).unwrap().permissions().mode() & 0o777;
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        assert!(make_executable(&file).unwrap());
+        assert_eq!(mode(&file), 0o750);
+        assert!(!make_executable(&file).unwrap());
+        assert!(make_executable(&ws.root().join("missing.sh")).is_err());
+    }
+}
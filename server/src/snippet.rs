@@ -0,0 +1,275 @@
+//! Tab stops in rendered headers.
+//!
+//! Templates may contain `${1:Brief description}` or `${2:}` tab stops, plus
+//! the `{cursor}` marker for the final caret position. Clients that accept
+//! snippet edits get them as an LSP snippet; everyone else gets plain text
+//! with the default values filled in (see `Header::new`).
+//!
+//! Only the `${N:default}` form is recognised, so `$1`, `"${1}"` and
+//! `${1:-fallback}` in a shell body or `$Id$` keywords pass through
+//! untouched. `$${N:...}` is a literal `${N:...}`; any other `$${`, such
+//! as Makefile `$${VAR}`, is left as written. Substituted values go
+//! through `literal` so they are never read as tab stops.
+
+/// What `{cursor}` renders to, marking where the editor should put the
+/// caret. A private-use character, so literal text such as an escaped
+/// `{{cursor}}` can never be mistaken for it.
+pub const CURSOR_MARKER: &str = "\u{E000}";
+
+#[derive(Debug, PartialEq)]
+pub enum Segment {
+    Text(String),
+    TabStop { index: u32, default: String },
+    Cursor,
+}
+
+/// Split rendered header text into literal text, tab stops and the cursor.
+pub fn parse(rendered: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = rendered;
+
+    while !rest.is_empty() {
+        if let Some(len) = rest.strip_prefix('$').and_then(tab_stop_len) {
+            text.push_str(&rest[1..1 + len]);
+            rest = &rest[1 + len..];
+        } else if let Some(after) = rest.strip_prefix(CURSOR_MARKER) {
+            flush(&mut segments, &mut text);
+            segments.push(Segment::Cursor);
+            rest = after;
+        } else if let Some((tab_stop, len)) = parse_tab_stop(rest) {
+            flush(&mut segments, &mut text);
+            segments.push(tab_stop);
+            rest = &rest[len..];
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    flush(&mut segments, &mut text);
+    segments
+}
+
+fn flush(segments: &mut Vec<Segment>, text: &mut String) {
+    if !text.is_empty() {
+        segments.push(Segment::Text(std::mem::take(text)));
+    }
+}
+
+/// Parse `${N:default}` at the start of `input`. A `\}` inside the default
+/// is a literal brace. `${N:-x}`, `${N:=x}`, `${N:?x}` and `${N:+x}` are
+/// shell parameter expansions, not tab stops.
+fn parse_tab_stop(input: &str) -> Option<(Segment, usize)> {
+    let body = input.strip_prefix("${")?;
+    let digits = body.chars().take_while(char::is_ascii_digit).count();
+    let index: u32 = body[..digits].parse().ok()?;
+
+    let default_src = body[digits..].strip_prefix(':')?;
+    if default_src.starts_with(['-', '=', '?', '+']) {
+        return None;
+    }
+    let mut default = String::new();
+    let mut chars = default_src.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, next @ ('}' | '$' | '\\'))) => default.push(next),
+                Some((_, next)) => {
+                    default.push('\\');
+                    default.push(next);
+                }
+                None => return None,
+            },
+            '}' => {
+                let len = 2 + digits + 1 + i + 1;
+                return Some((Segment::TabStop { index, default }, len));
+            }
+            '\n' => return None,
+            c => default.push(c),
+        }
+    }
+    None
+}
+
+/// Length in bytes of the `${N:default}` tab stop at the start of `input`,
+/// so line wrapping can keep it whole.
+pub fn tab_stop_len(input: &str) -> Option<usize> {
+    parse_tab_stop(input).map(|(_, len)| len)
+}
+
+/// `text` with a `$` put before every tab stop in it, so `parse` reads it
+/// back unchanged.
+pub fn literal(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if tab_stop_len(rest).is_some() {
+            out.push('$');
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// `${index:default}` with `default` escaped so `parse` gets it back.
+pub fn tab_stop(index: u32, default: &str) -> String {
+    format!("${{{index}:{}}}", escape(default))
+}
+
+/// Highest tab stop index in `text`, or 0 if there are none.
+pub fn max_index(text: &str) -> u32 {
+    parse(text)
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::TabStop { index, .. } => Some(*index),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Whether the segments contain any tab stop worth sending as a snippet.
+pub fn has_tab_stops(segments: &[Segment]) -> bool {
+    segments
+        .iter()
+        .any(|s| matches!(s, Segment::TabStop { .. }))
+}
+
+/// LSP snippet syntax: literal text escaped, `{cursor}` becomes `$0`.
+pub fn to_snippet(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => out.push_str(&escape(text)),
+            Segment::TabStop { index, default } if default.is_empty() => {
+                out.push_str(&format!("${{{index}}}"));
+            }
+            Segment::TabStop { index, default } => {
+                out.push_str(&format!("${{{index}:{}}}", escape(default)));
+            }
+            Segment::Cursor => out.push_str("$0"),
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '$' | '}' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_stops_and_cursor_are_recognised() {
+        let segments = parse(&format!(
+            "File: x\n${{1:Brief description}}\n${{2:}}{CURSOR_MARKER}"
+        ));
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Text("File: x\n".into()),
+                Segment::TabStop {
+                    index: 1,
+                    default: "Brief description".into()
+                },
+                Segment::Text("\n".into()),
+                Segment::TabStop {
+                    index: 2,
+                    default: String::new()
+                },
+                Segment::Cursor,
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_brace_in_default() {
+        let segments = parse("${1:TODO \\} here}");
+        assert_eq!(
+            segments,
+            vec![Segment::TabStop {
+                index: 1,
+                default: "TODO } here".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn snippet_escapes_literal_dollars_and_braces() {
+        let segments = parse(&format!(
+            "echo \"$1 ${{HOME}}\" # $Id$\n${{1:what}} {CURSOR_MARKER}"
+        ));
+        assert!(has_tab_stops(&segments));
+        assert_eq!(
+            to_snippet(&segments),
+            "echo \"\\$1 \\${HOME\\}\" # \\$Id\\$\n${1:what} $0"
+        );
+    }
+
+    #[test]
+    fn built_tab_stops_parse_back() {
+        let text = format!("${{2:}} {}", tab_stop(5, "a } $b"));
+        assert_eq!(max_index(&text), 5);
+        assert_eq!(
+            parse(&text)[2],
+            Segment::TabStop {
+                index: 5,
+                default: "a } $b".into()
+            }
+        );
+        assert_eq!(max_index("no stops"), 0);
+    }
+
+    #[test]
+    fn shell_expansions_stay_literal_and_dollar_dollar_escapes() {
+        let body = "set -- \"${1}\" \"${2:-dev}\" ${3:=x} ${4:?} ${5:+y}\necho $${1:not a stop}\n${1:name}";
+        let segments = parse(body);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Text(
+                    "set -- \"${1}\" \"${2:-dev}\" ${3:=x} ${4:?} ${5:+y}\necho ${1:not a stop}\n"
+                        .into()
+                ),
+                Segment::TabStop {
+                    index: 1,
+                    default: "name".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn other_dollar_dollar_braces_are_left_alone() {
+        let body = "all:\n\techo $${HOME} $$x\n";
+        assert_eq!(parse(body), vec![Segment::Text(body.into())]);
+    }
+
+    #[test]
+    fn literal_values_parse_back_unchanged() {
+        for value in ["${1:x}", "$${1:x}", "a ${2:b c} $${HOME} $", "$$${3:}"] {
+            assert_eq!(
+                parse(&literal(value)),
+                vec![Segment::Text(value.into())],
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn unbraced_and_unterminated_forms_are_literal() {
+        let segments = parse("$1 ${x:y} ${3:open");
+        assert_eq!(segments, vec![Segment::Text("$1 ${x:y} ${3:open".into())]);
+    }
+}
@@ -0,0 +1,606 @@
+//! Placeholder substitution for header templates.
+//!
+//! A placeholder is `{name}` optionally followed by pipe filters, e.g.
+//! `{filename|stem|upper|ident}` or `{project|default("Untitled")}`.
+//! `{env:VAR}` and `{env:VAR:-fallback}` read the process environment
+//! (unless `vars` has an `env:VAR` entry, which tests use instead), and
+//! `{prompt:Label}` takes its value from the variable `prompt:Label`.
+//! `{{` and `}}` are literal braces, and a brace right after `$` is never a
+//! placeholder, so `${1:tab stops}` and shell `${VAR}` pass through.
+//! Anything in braces that doesn't parse as a placeholder is left in the
+//! output verbatim; so are placeholders naming an unknown variable or filter,
+//! unless the caller asks for them to be removed (see `render_reporting`).
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Variables available to a template, keyed by placeholder name.
+pub type Vars<'a> = HashMap<&'a str, String>;
+
+/// Substitute every placeholder in `template`. Multi-line values have their
+/// continuation lines indented to the placeholder's column so lists such as
+/// `Authors: {authors}` stay aligned.
+pub fn render(template: &str, vars: &Vars) -> String {
+    render_reporting(template, vars, false, &|_, value| value).0
+}
+
+/// Like `render`, but also returns the placeholders that couldn't be
+/// resolved, as written (`{typo}`). With `remove_unresolved` they are left
+/// out of the output instead of kept verbatim, together with the space
+/// that separated them, so no double or trailing spaces remain.
+///
+/// `escape` gets each placeholder's name and value before it is
+/// substituted, so values can be kept apart from syntax that the output is
+/// parsed for later (tab stops, see `snippet::literal`).
+pub fn render_reporting(
+    template: &str,
+    vars: &Vars,
+    remove_unresolved: bool,
+    escape: &dyn Fn(&str, String) -> String,
+) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(template.len());
+    let mut unresolved = Vec::new();
+    let mut removed = false;
+    for token in tokens(template) {
+        match token {
+            Token::Text(mut text) => {
+                if std::mem::take(&mut removed) {
+                    text = close_gap(&mut out, text);
+                }
+                out.push_str(text);
+            }
+            Token::Placeholder {
+                placeholder, raw, ..
+            } => match placeholder.evaluate(vars) {
+                Some(value) => {
+                    removed = false;
+                    push_aligned(&mut out, &escape(placeholder.name, value));
+                }
+                None => {
+                    if remove_unresolved {
+                        removed = true;
+                    } else {
+                        out.push_str(raw);
+                    }
+                    if !unresolved.iter().any(|u| u == raw) {
+                        unresolved.push(raw.to_string());
+                    }
+                }
+            },
+        }
+    }
+    (out, unresolved)
+}
+
+/// Join `out` and the `text` following a removed placeholder: drop the
+/// space before `text` if `out` already ends a word there, or the spaces
+/// `out` ends with if the line ends. Returns what is left of `text`.
+fn close_gap<'a>(out: &mut String, text: &'a str) -> &'a str {
+    let at_word_break = out.is_empty() || out.ends_with([' ', '\n']);
+    match text.strip_prefix(' ') {
+        Some(rest) if at_word_break => rest,
+        _ if text.is_empty() || text.starts_with('\n') => {
+            out.truncate(out.trim_end_matches(' ').len());
+            text
+        }
+        _ => text,
+    }
+}
+
+enum Token<'a> {
+    /// Literal output, including the single brace of a `{{`/`}}` escape.
+    Text(&'a str),
+    Placeholder {
+        placeholder: Placeholder<'a>,
+        /// The placeholder as written, braces included.
+        raw: &'a str,
+        /// Byte offset of the opening brace.
+        offset: usize,
+    },
+}
+
+/// Split `template` into literal text and placeholders.
+fn tokens(template: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    let bytes = template.as_bytes();
+    while i < bytes.len() {
+        let escape = matches!(&bytes[i..], [b'{', b'{', ..] | [b'}', b'}', ..]);
+        if escape {
+            // Keep the first brace, drop the second
+            tokens.push(Token::Text(&template[text_start..=i]));
+            i += 2;
+            text_start = i;
+            continue;
+        }
+        if bytes[i] == b'{' && (i == 0 || bytes[i - 1] != b'$') {
+            if let Some((placeholder, len)) = parse_placeholder(&template[i..]) {
+                tokens.push(Token::Text(&template[text_start..i]));
+                tokens.push(Token::Placeholder {
+                    placeholder,
+                    raw: &template[i..i + len],
+                    offset: i,
+                });
+                i += len;
+                text_start = i;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    tokens.push(Token::Text(&template[text_start..]));
+    tokens
+}
+
+/// Whether `template` contains a placeholder for variable `name`, with or
+/// without filters. Used to skip expensive lookups nobody asked for.
+pub fn uses(template: &str, name: &str) -> bool {
+    placeholder_names(template).contains(&name)
+}
+
+/// Labels of the `{prompt:Label}` placeholders in `template`, in order of
+/// first appearance.
+pub fn prompts(template: &str) -> Vec<&str> {
+    let mut labels = Vec::new();
+    for name in placeholder_names(template) {
+        if let Some(label) = name.strip_prefix(PROMPT_PREFIX) {
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+    }
+    labels
+}
+
+/// Variable key under which the answer to `{prompt:label}` is looked up.
+pub fn prompt_key(label: &str) -> String {
+    format!("{PROMPT_PREFIX}{label}")
+}
+
+const PROMPT_PREFIX: &str = "prompt:";
+
+/// A placeholder as written in a template, for config validation.
+#[derive(Debug, PartialEq)]
+pub struct PlaceholderRef<'a> {
+    /// Byte offset of the opening brace.
+    pub offset: usize,
+    pub name: &'a str,
+    pub filters: Vec<&'a str>,
+}
+
+/// Every placeholder in `template`, in order.
+pub fn placeholders(template: &str) -> Vec<PlaceholderRef<'_>> {
+    tokens(template)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Placeholder {
+                placeholder,
+                offset,
+                ..
+            } => Some(PlaceholderRef {
+                offset,
+                name: placeholder.name,
+                filters: placeholder.filters.iter().map(|f| f.name).collect(),
+            }),
+            Token::Text(_) => None,
+        })
+        .collect()
+}
+
+/// Names of every placeholder in `template`, filters stripped.
+fn placeholder_names(template: &str) -> Vec<&str> {
+    placeholders(template).into_iter().map(|p| p.name).collect()
+}
+
+/// Append `value`, indenting any continuation lines to the current column.
+fn push_aligned(out: &mut String, value: &str) {
+    let line_start = out.rfind('\n').map_or(0, |i| i + 1);
+    let column = out[line_start..].chars().count();
+    for (i, line) in value.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+            if !line.is_empty() {
+                out.extend(std::iter::repeat_n(' ', column));
+            }
+        }
+        out.push_str(line);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Placeholder<'a> {
+    name: &'a str,
+    filters: Vec<Filter<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Filter<'a> {
+    name: &'a str,
+    arg: Option<String>,
+}
+
+impl Placeholder<'_> {
+    fn evaluate(&self, vars: &Vars) -> Option<String> {
+        let mut value = match self.name.strip_prefix("env:") {
+            Some(spec) => env_value(spec, vars),
+            None => vars.get(self.name)?.clone(),
+        };
+        for filter in &self.filters {
+            value = apply_filter(filter, value)?;
+        }
+        Some(value)
+    }
+}
+
+/// `VAR` or `VAR:-fallback`; the fallback applies when unset or empty. An
+/// `env:VAR` entry in `vars` takes the place of the process environment.
+fn env_value(spec: &str, vars: &Vars) -> String {
+    let (name, fallback) = spec.split_once(":-").unwrap_or((spec, ""));
+    let value = match vars.get(format!("env:{}", name).as_str()) {
+        Some(value) => Some(value.clone()),
+        None => std::env::var(name).ok(),
+    };
+    match value {
+        Some(value) if !value.is_empty() => value,
+        _ => fallback.to_string(),
+    }
+}
+
+/// Parse a placeholder starting at the `{` at the beginning of `input`.
+/// Returns the placeholder and its length in bytes including both braces.
+fn parse_placeholder(input: &str) -> Option<(Placeholder<'_>, usize)> {
+    let close = find_close(input)?;
+    let body = &input[1..close];
+
+    // Split on `|` outside of quoted filter arguments
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '|' if !in_quotes => {
+                parts.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&body[start..]);
+
+    let mut parts = parts.into_iter();
+    let name = parts.next()?.trim();
+    if !is_valid_name(name) {
+        return None;
+    }
+    let filters = parts.map(parse_filter).collect::<Option<Vec<_>>>()?;
+
+    Some((Placeholder { name, filters }, close + 1))
+}
+
+/// Index of the `}` closing the placeholder, skipping braces inside quotes.
+/// Placeholders never span lines.
+fn find_close(input: &str) -> Option<usize> {
+    let mut in_quotes = false;
+    for (i, c) in input.char_indices().skip(1) {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '}' if !in_quotes => return Some(i),
+            '{' | '\n' if !in_quotes => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_valid_name(name: &str) -> bool {
+    // Prompt labels are shown to the user, so allow a readable sentence
+    if let Some(label) = name.strip_prefix(PROMPT_PREFIX) {
+        return !label.trim().is_empty()
+            && label
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.'));
+    }
+    if let Some(spec) = name.strip_prefix("env:") {
+        let var = spec.split_once(":-").map_or(spec, |(var, _)| var);
+        return !var.is_empty() && var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    }
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_filter(spec: &str) -> Option<Filter<'_>> {
+    let spec = spec.trim();
+    let (name, arg) = match spec.split_once('(') {
+        Some((name, rest)) => {
+            let raw = rest.strip_suffix(')')?.trim();
+            let arg = match raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+                Some(quoted) => quoted.to_string(),
+                None => raw.to_string(),
+            };
+            (name.trim(), Some(arg))
+        }
+        None => (spec, None),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(Filter { name, arg })
+}
+
+/// Filters understood by `apply_filter`, with a short description, for
+/// completion and hover in `.auto-header.toml`.
+pub const FILTERS: &[(&str, &str)] = &[
+    ("upper", "UPPER CASE"),
+    ("lower", "lower case"),
+    ("trim", "Strip surrounding whitespace"),
+    ("snake", "snake_case"),
+    ("kebab", "kebab-case"),
+    ("camel", "camelCase"),
+    ("pascal", "PascalCase"),
+    (
+        "ident",
+        "Replace anything but ASCII letters and digits with `_`",
+    ),
+    ("stem", "File name without its extension"),
+    (
+        "default",
+        "`default(\"x\")`: use `x` when the value is empty",
+    ),
+    (
+        "pad",
+        "`pad(n)`: pad with spaces to at least `n` characters",
+    ),
+];
+
+/// Apply one filter. Returns `None` for unknown filters or bad arguments so
+/// the placeholder is left untouched rather than silently mangled.
+fn apply_filter(filter: &Filter, value: String) -> Option<String> {
+    let result = match (filter.name, filter.arg.as_deref()) {
+        ("upper", None) => value.to_uppercase(),
+        ("lower", None) => value.to_lowercase(),
+        ("trim", None) => value.trim().to_string(),
+        ("snake", None) => words(&value).join("_").to_lowercase(),
+        ("kebab", None) => words(&value).join("-").to_lowercase(),
+        ("camel", None) => {
+            let mut out = String::new();
+            for (i, word) in words(&value).iter().enumerate() {
+                if i == 0 {
+                    out.push_str(&word.to_lowercase());
+                } else {
+                    out.push_str(&capitalize(word));
+                }
+            }
+            out
+        }
+        ("pascal", None) => words(&value).iter().map(|w| capitalize(w)).collect(),
+        ("ident", None) => {
+            let mut out: String = value
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            if out.starts_with(|c: char| c.is_ascii_digit()) {
+                out.insert(0, '_');
+            }
+            out
+        }
+        ("stem", None) => Path::new(&value)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&value)
+            .to_string(),
+        ("default", Some(fallback)) => {
+            if value.trim().is_empty() {
+                fallback.to_string()
+            } else {
+                value
+            }
+        }
+        ("pad", Some(width)) => {
+            let width: usize = width.parse().ok()?;
+            format!("{value:<width$}")
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Split into words on non-alphanumeric characters and case boundaries, so
+/// `my-file.name`, `my_file_name` and `MyFileName` all yield the same words.
+/// Acronyms stay together: `HTTPServer` → `HTTP`, `Server`.
+fn words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = value.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(entries: &[(&'static str, &str)]) -> Vars<'static> {
+        entries.iter().map(|(k, v)| (*k, v.to_string())).collect()
+    }
+
+    #[test]
+    fn plain_variables_are_substituted() {
+        let v = vars(&[("filename", "main.rs"), ("year", "2026")]);
+        assert_eq!(render("{filename} ({year})", &v), "main.rs (2026)");
+    }
+
+    #[test]
+    fn unknown_variables_and_stray_braces_are_left_alone() {
+        let v = vars(&[("filename", "a.c")]);
+        assert_eq!(
+            render("{typo} {filename} { not a var } {", &v),
+            "{typo} a.c { not a var } {"
+        );
+        assert_eq!(render("{filename|nope}", &v), "{filename|nope}");
+    }
+
+    #[test]
+    fn include_guard_filters() {
+        let v = vars(&[("filename", "my-widget.h")]);
+        assert_eq!(
+            render("#ifndef {filename|upper|ident}_H", &v),
+            "#ifndef MY_WIDGET_H_H"
+        );
+        assert_eq!(
+            render("#ifndef {filename|stem|upper|ident}_H", &v),
+            "#ifndef MY_WIDGET_H"
+        );
+    }
+
+    #[test]
+    fn case_conversion_filters() {
+        let v = vars(&[("name", "HTTPServer config-loader")]);
+        assert_eq!(render("{name|snake}", &v), "http_server_config_loader");
+        assert_eq!(render("{name|kebab}", &v), "http-server-config-loader");
+        assert_eq!(render("{name|camel}", &v), "httpServerConfigLoader");
+        assert_eq!(render("{name|pascal}", &v), "HttpServerConfigLoader");
+        assert_eq!(render("{name|lower}", &v), "httpserver config-loader");
+    }
+
+    #[test]
+    fn ident_prefixes_leading_digit() {
+        let v = vars(&[("filename", "3d.h")]);
+        assert_eq!(render("{filename|ident}", &v), "_3d_h");
+    }
+
+    #[test]
+    fn default_trim_and_pad_filters() {
+        let v = vars(&[("empty", ""), ("name", "  x  ")]);
+        assert_eq!(render("{empty|default(\"n/a\")}", &v), "n/a");
+        assert_eq!(render("{empty|default(\"a|b}\")}", &v), "a|b}");
+        assert_eq!(render("[{name|trim}]", &v), "[x]");
+        assert_eq!(render("[{name|trim|pad(4)}]", &v), "[x   ]");
+        assert_eq!(render("{name|pad(x)}", &v), "{name|pad(x)}");
+    }
+
+    #[test]
+    fn env_placeholders_accept_filters() {
+        let v = vars(&[("env:AUTO_HEADER_TEMPLATE_TEST", "ci-bot")]);
+        assert_eq!(
+            render("{env:AUTO_HEADER_TEMPLATE_TEST|upper}", &v),
+            "CI-BOT"
+        );
+        assert_eq!(
+            render("{env:AUTO_HEADER_TEMPLATE_UNSET:-some one|pascal}", &v),
+            "SomeOne"
+        );
+    }
+
+    #[test]
+    fn multiline_values_are_aligned_to_placeholder_column() {
+        let v = vars(&[("authors", "Alice\nBob")]);
+        assert_eq!(
+            render("Authors: {authors}\nDate: x", &v),
+            "Authors: Alice\n         Bob\nDate: x"
+        );
+    }
+
+    #[test]
+    fn prompt_placeholders_are_listed_once_and_rendered_from_vars() {
+        let template = "{prompt:Ticket number} {prompt:Description|upper}\n{prompt:Ticket number}";
+        assert_eq!(prompts(template), vec!["Ticket number", "Description"]);
+
+        let key = prompt_key("Description");
+        let mut v = Vars::new();
+        v.insert(&key, "parser".to_string());
+        assert_eq!(
+            render(template, &v),
+            "{prompt:Ticket number} PARSER\n{prompt:Ticket number}"
+        );
+    }
+
+    #[test]
+    fn uses_detects_filtered_placeholders() {
+        assert!(uses("Owner: {owners|upper}", "owners"));
+        assert!(!uses("Owner: {owner}", "owners"));
+    }
+
+    #[test]
+    fn every_documented_filter_is_implemented() {
+        for (name, _) in FILTERS {
+            let arg = match *name {
+                "default" => Some("x".to_string()),
+                "pad" => Some("3".to_string()),
+                _ => None,
+            };
+            let filter = Filter { name, arg };
+            assert!(apply_filter(&filter, "a".into()).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        let v = vars(&[("filename", "a.c")]);
+        assert_eq!(
+            render("{{filename}} = {filename}, }} {{", &v),
+            "{filename} = a.c, } {"
+        );
+        assert!(placeholders("{{filename}}").is_empty());
+    }
+
+    #[test]
+    fn dollar_brace_is_never_a_placeholder() {
+        let v = vars(&[("HOME", "x"), ("2", "y")]);
+        let (out, unresolved) = render_reporting("${HOME} ${2} ${1:a}", &v, true, &|_, v| v);
+        assert_eq!(out, "${HOME} ${2} ${1:a}");
+        assert!(unresolved.is_empty());
+    }
+
+    #[test]
+    fn unresolved_placeholders_are_reported_and_optionally_removed() {
+        let v = vars(&[("filename", "a.c")]);
+        let template = "File: {filname} {filename|nope} {filename} {filname}";
+        let (kept, unresolved) = render_reporting(template, &v, false, &|_, v| v);
+        assert_eq!(kept, "File: {filname} {filename|nope} a.c {filname}");
+        assert_eq!(unresolved, vec!["{filname}", "{filename|nope}"]);
+
+        let (removed, _) = render_reporting(template, &v, true, &|_, v| v);
+        assert_eq!(removed, "File: a.c");
+        let (removed, _) = render_reporting(
+            "{typo} lead\nmid {typo} dle\ntail: {typo}\n",
+            &v,
+            true,
+            &|_, v| v,
+        );
+        assert_eq!(removed, "lead\nmid dle\ntail:\n");
+    }
+}
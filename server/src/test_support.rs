@@ -0,0 +1,40 @@
+//! Shared helpers for unit tests that need real files on disk.
+
+use crate::{Config, ConfigSource};
+use std::path::{Path, PathBuf};
+
+/// The project config in `root` as the only source, so tests never read
+/// the real home or platform config.
+pub fn project_sources(root: &Path) -> Vec<ConfigSource> {
+    Config::sources_at(vec![("project", root.join(".auto-header.toml"))])
+}
+
+/// A scratch workspace under the system temp dir, removed on drop.
+pub struct Workspace(PathBuf);
+
+impl Workspace {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("auto-header-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.0
+    }
+
+    /// Create `relative` (and its parent directories) with `content`.
+    pub fn write(&self, relative: &str, content: &str) -> PathBuf {
+        let path = self.0.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
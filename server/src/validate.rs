@@ -0,0 +1,366 @@
+//! Config file checks for `strict = true` and the `validate` subcommand:
+//! keys the schema doesn't know (serde silently drops them), unknown
+//! placeholders and filters in templates, values that don't deserialize
+//! (which makes the loader ignore the whole file), and `by_extension`
+//! entries for extensions without a built-in comment style.
+
+use crate::{schema, template, ConfigSource, HeaderProfile, PartialConfig, TEMPLATE_VARIABLES};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml_edit::{ImDocument, Item, TableLike};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// The config doesn't do what it says; `strict = true` refuses to insert.
+    Error,
+    /// Works, but probably not as intended.
+    Warning,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    pub path: PathBuf,
+    /// 1-based.
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// `path:line: message`, like a compiler diagnostic, with `warning: `
+/// before the message of warnings.
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.path.display(), self.line)?;
+        if self.severity == Severity::Warning {
+            write!(f, "warning: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Check every config file that was found, highest priority first.
+pub fn validate_sources(sources: &[ConfigSource]) -> Vec<Problem> {
+    sources
+        .iter()
+        .flat_map(|source| validate_file(&source.path))
+        .collect()
+}
+
+/// Read and check the config file at `path`.
+pub fn validate_file(path: &Path) -> Vec<Problem> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => validate(path, &content),
+        Err(e) => vec![Problem {
+            path: path.to_path_buf(),
+            line: 1,
+            severity: Severity::Error,
+            message: format!("cannot read file: {}", e),
+        }],
+    }
+}
+
+/// Check `content`, reporting problems against `path`.
+pub fn validate(path: &Path, content: &str) -> Vec<Problem> {
+    let mut checker = Checker {
+        path,
+        content,
+        schema: schema::json_schema(),
+        problems: Vec::new(),
+    };
+    match ImDocument::parse(content) {
+        Ok(doc) => {
+            let root = schema::resolve(checker.schema, checker.schema).clone();
+            checker.table(doc.as_table(), &root, &[]);
+            // Known keys can still hold a wrong type or an unknown variant
+            if let Err(e) = toml::from_str::<PartialConfig>(content) {
+                let offset = e.span().map_or(0, |span| span.start);
+                let message = format!("{} (the whole file is ignored)", e.message().trim_end());
+                checker.report(offset, message);
+            }
+        }
+        Err(e) => {
+            let offset = e.span().map_or(0, |span| span.start);
+            checker.report(offset, e.message().to_string());
+        }
+    }
+    checker.problems.sort_by_key(|p| p.line);
+    checker.problems
+}
+
+struct Checker<'a> {
+    path: &'a Path,
+    content: &'a str,
+    schema: &'static Value,
+    problems: Vec<Problem>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, offset: usize, message: String) {
+        self.push(offset, Severity::Error, message);
+    }
+
+    fn warn(&mut self, offset: usize, message: String) {
+        self.push(offset, Severity::Warning, message);
+    }
+
+    fn push(&mut self, offset: usize, severity: Severity, message: String) {
+        let offset = offset.min(self.content.len());
+        self.problems.push(Problem {
+            path: self.path.to_path_buf(),
+            line: self.content[..offset].matches('\n').count() + 1,
+            severity,
+            message,
+        });
+    }
+
+    /// Check every key of `table` (whose schema is `node`) at `path`.
+    fn table(&mut self, table: &dyn TableLike, node: &Value, path: &[&str]) {
+        for (key, item) in table.iter() {
+            let offset = table
+                .key(key)
+                .and_then(|k| k.span())
+                .or_else(|| item.span())
+                .map_or(0, |span| span.start);
+            let mut child_path = path.to_vec();
+            child_path.push(key);
+
+            let Some(child) = schema::child(self.schema, node, key).cloned() else {
+                let known = schema::property_names(node);
+                let mut message = format!("unknown key `{}`", child_path.join("."));
+                if let Some(close) = closest(key, &known) {
+                    message.push_str(&format!(" (did you mean `{}`?)", close));
+                }
+                self.report(offset, message);
+                continue;
+            };
+
+            if let [.., "by_extension", ext] = child_path.as_slice() {
+                // A `profile` picks the comment style itself
+                let has_profile = item
+                    .as_table_like()
+                    .is_some_and(|entry| entry.contains_key("profile"));
+                if !HeaderProfile::KNOWN_EXTENSIONS.contains(ext) && !has_profile {
+                    self.warn(
+                        offset,
+                        format!(
+                            "no built-in comment style for `.{}`; its header will use `#` line comments",
+                            ext
+                        ),
+                    );
+                }
+            }
+
+            self.item(item, &child, &child_path);
+        }
+    }
+
+    fn item(&mut self, item: &Item, node: &Value, path: &[&str]) {
+        match item {
+            Item::Table(table) => self.table(table, node, path),
+            Item::ArrayOfTables(tables) => {
+                for table in tables.iter() {
+                    self.table(table, node, path);
+                }
+            }
+            Item::Value(toml_edit::Value::InlineTable(table)) => self.table(table, node, path),
+            Item::Value(toml_edit::Value::Array(array)) => {
+                for value in array.iter() {
+                    if let toml_edit::Value::InlineTable(table) = value {
+                        self.table(table, node, path);
+                    }
+                }
+            }
+            Item::Value(toml_edit::Value::String(s)) => {
+                if matches!(path.last(), Some(&"template" | &"body")) {
+                    let mut start = s.span().map_or(0, |span| span.start);
+                    // A newline right after `"""` isn't part of the value
+                    let raw = &self.content[start..];
+                    if raw.starts_with("\"\"\"") || raw.starts_with("'''") {
+                        if let Some(rest) = raw[3..].strip_prefix(['\n', '\r']) {
+                            start = self.content.len() - rest.trim_start_matches('\n').len();
+                        }
+                    }
+                    self.placeholders(s.value(), start);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Report unknown placeholders and filters in a template whose raw
+    /// string starts at byte `start` of the file.
+    fn placeholders(&mut self, template: &str, start: usize) {
+        let (prefixes, variables): (Vec<&str>, Vec<&str>) = TEMPLATE_VARIABLES
+            .iter()
+            .chain(schema::SPECIAL_PLACEHOLDERS)
+            .map(|(name, _)| *name)
+            .partition(|name| name.ends_with(':'));
+        let filters: Vec<&str> = template::FILTERS.iter().map(|(name, _)| *name).collect();
+
+        for placeholder in template::placeholders(template) {
+            // Escapes in the raw string shift columns, not lines (except for
+            // rare line-ending backslashes)
+            let lines_in = template[..placeholder.offset].matches('\n').count();
+            let offset = nth_line_start(self.content, start, lines_in);
+
+            let name = placeholder.name;
+            let prefixed = prefixes.iter().any(|prefix| name.starts_with(prefix));
+            if !prefixed && !variables.contains(&name) {
+                let mut message = format!("unknown placeholder `{{{}}}`", name);
+                if let Some(close) = closest(name, &variables) {
+                    message.push_str(&format!(" (did you mean `{{{}}}`?)", close));
+                }
+                self.report(offset, message);
+            }
+            for filter in placeholder.filters {
+                if !filters.contains(&filter) {
+                    let mut message = format!("unknown filter `{}` in `{{{}}}`", filter, name);
+                    if let Some(close) = closest(filter, &filters) {
+                        message.push_str(&format!(" (did you mean `{}`?)", close));
+                    }
+                    self.report(offset, message);
+                }
+            }
+        }
+    }
+}
+
+/// Byte offset of the `n`th line after the one containing `start`.
+fn nth_line_start(content: &str, start: usize, n: usize) -> usize {
+    let mut offset = start;
+    for _ in 0..n {
+        match content[offset..].find('\n') {
+            Some(i) => offset += i + 1,
+            None => break,
+        }
+    }
+    offset
+}
+
+/// The candidate within two edits of `word`, if any, for "did you mean".
+fn closest<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|c| (edit_distance(word, c), *c))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(content: &str) -> Vec<String> {
+        validate(Path::new("cfg.toml"), content)
+            .iter()
+            .map(Problem::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn unknown_keys_are_reported_with_suggestions() {
+        let problems = check("[autor]\nname = \"x\"\n\n[header]\ntempalte = \"x\"\n");
+        assert_eq!(
+            problems,
+            vec![
+                "cfg.toml:1: unknown key `autor` (did you mean `author`?)",
+                "cfg.toml:5: unknown key `header.tempalte` (did you mean `template`?)",
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_tables_arrays_and_maps_are_checked() {
+        let content = r#"
+[[authors]]
+name = "A"
+mail = "a@x"
+
+[prompts.Team]
+options = ["a"]
+defualt = "a"
+
+[header.by_extension.rs]
+template = "ok"
+"#;
+        let problems = check(content);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].starts_with("cfg.toml:4: unknown key `authors.mail`"));
+        assert!(problems[1].starts_with("cfg.toml:8: unknown key `prompts.Team.defualt`"));
+    }
+
+    #[test]
+    fn unknown_placeholders_and_filters_point_at_their_line() {
+        let content = "[header]\ntemplate = \"\"\"\nFile: {filname}\n{year|uper} {env:USER} {prompt:Team} {cursor}\n\"\"\"\n";
+        assert_eq!(
+            check(content),
+            vec![
+                "cfg.toml:3: unknown placeholder `{filname}` (did you mean `{filename}`?)",
+                "cfg.toml:4: unknown filter `uper` in `{year}` (did you mean `upper`?)",
+            ]
+        );
+    }
+
+    #[test]
+    fn extensions_without_a_profile_are_flagged() {
+        let problems = check(
+            "[header.by_extension.xyz]\ntemplate = \"x\"\n[header.by_extension.py]\n[header.by_extension.mjs]\nprofile = \"jsdoc\"\n",
+        );
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].starts_with("cfg.toml:1: warning: no built-in comment style for `.xyz`")
+        );
+    }
+
+    #[test]
+    fn values_that_do_not_deserialize_are_errors() {
+        let problems = check("[header]\nbox_style = \"starz\"\n");
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(
+            problems[0].starts_with("cfg.toml:2: unknown variant `starz`"),
+            "{:?}",
+            problems
+        );
+
+        let problems = check("strict = true\nenabled = \"no\"\n");
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].starts_with("cfg.toml:2: "), "{:?}", problems);
+        assert!(problems[0].ends_with("(the whole file is ignored)"));
+    }
+
+    #[test]
+    fn syntax_errors_are_located() {
+        let problems = check("[author]\nname = \n");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("cfg.toml:2: "), "{:?}", problems);
+    }
+
+    #[test]
+    fn valid_config_has_no_problems() {
+        let sample = include_str!("../../.auto-header.toml");
+        assert_eq!(check(sample), Vec::<String>::new());
+    }
+}